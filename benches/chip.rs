@@ -0,0 +1,144 @@
+// cargo bench, criterion keeps the last run in target/criterion and reports regressions against it
+// runs the roms in benches/roms, extra roms: CHIP_BENCH_ROMS=path/to/roms cargo bench
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use std::{env, fs};
+
+use chiploxide::chip::{
+    clock::Tick,
+    dynarec::Dynarec,
+    screen::{Screen, PIXELS},
+    Cpu,
+};
+#[cfg(feature = "bevy")]
+use chiploxide::SyncBench;
+
+const CYCLES: usize = 10_000;
+const ON: u32 = 0xFFFF_FFFF;
+const OFF: u32 = 0xFF00_0000;
+
+/// synthetic loops, each spends nearly all of its time on one kind of op
+fn workloads() -> Vec<(String, Cpu)> {
+    let mut workloads = vec![
+        (
+            "alu".to_string(),
+            Cpu::load(&[
+                0x70, 0x01, // 200: ADD  V0, 01
+                0x81, 0x04, // 202: ADD  V1, V0
+                0x82, 0x15, // 204: SUB  V2, V1
+                0x83, 0x21, // 206: OR   V3, V2
+                0x84, 0x32, // 208: AND  V4, V3
+                0x85, 0x43, // 20A: XOR  V5, V4
+                0x86, 0x06, // 20C: SHR  V6
+                0x87, 0x0E, // 20E: SHL  V7
+                0x30, 0xFF, // 210: SE   V0, FF
+                0x12, 0x00, // 212: JP   200
+                0x12, 0x00, // 214: JP   200
+            ]),
+        ),
+        (
+            "draw".to_string(),
+            Cpu::load(&[
+                0xA2, 0x08, // 200: LD   I, 208
+                0xD0, 0x1F, // 202: DRW  V0, V1, F
+                0x70, 0x01, // 204: ADD  V0, 01
+                0x12, 0x02, // 206: JP   202
+                0xF0, 0x90, 0x90, 0x90, 0xF0, 0x20, 0x60, 0x20, // 208: sprite rows
+                0x20, 0x70, 0xF0, 0x10, 0xF0, 0x80, 0xF0, //
+            ]),
+        ),
+        (
+            "call".to_string(),
+            Cpu::load(&[
+                0x22, 0x04, // 200: CALL 204
+                0x12, 0x00, // 202: JP   200
+                0x22, 0x08, // 204: CALL 208
+                0x00, 0xEE, // 206: RET
+                0x70, 0x01, // 208: ADD  V0, 01
+                0x00, 0xEE, // 20A: RET
+            ]),
+        ),
+        ("demo".to_string(), Cpu::new()),
+    ];
+    let mut dirs = vec![concat!(env!("CARGO_MANIFEST_DIR"), "/benches/roms").to_string()];
+    dirs.extend(env::var("CHIP_BENCH_ROMS"));
+    for dir in dirs {
+        let mut entries: Vec<_> = fs::read_dir(&dir)
+            .unwrap_or_else(|e| panic!("{}: {}", dir, e))
+            .flatten()
+            .filter(|entry| entry.path().extension().is_some_and(|ext| ext == "ch8"))
+            .collect();
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            if let Ok(rom) = fs::read(entry.path()) {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                workloads.push((name, Cpu::load(&rom)));
+            }
+        }
+    }
+    workloads
+}
+fn throughput(c: &mut Criterion) {
+    let tick = Tick {
+        cycles: CYCLES,
+        timers: 0,
+    };
+    let mut group = c.benchmark_group("instructions");
+    group.throughput(Throughput::Elements(CYCLES as u64));
+    for (name, cpu) in workloads() {
+        group.bench_function(BenchmarkId::new("interpreter", &name), |b| {
+            b.iter_batched(|| cpu, |mut cpu| cpu.advance(tick), BatchSize::LargeInput)
+        });
+        let mut dynarec = Dynarec::new();
+        group.bench_function(BenchmarkId::new("dynarec", &name), |b| {
+            b.iter_batched(
+                || cpu,
+                |mut cpu| dynarec.advance(&mut cpu, tick),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+/// a second of the draw workload, one screen per frame
+fn frames() -> Vec<Screen> {
+    let (_, mut cpu) = workloads()
+        .into_iter()
+        .find(|(name, _)| name == "draw")
+        .expect("the draw workload");
+    (0..60)
+        .map(|_| {
+            cpu.advance(Tick {
+                cycles: 10,
+                timers: 1,
+            });
+            cpu.screen
+        })
+        .collect()
+}
+fn screen(c: &mut Criterion) {
+    let frames = frames();
+    let mut group = c.benchmark_group("screen");
+    group.throughput(Throughput::Elements(frames.len() as u64));
+    // the app's own pixels_sync and pixels_disable on one entity per pixel, without the renderer
+    #[cfg(feature = "bevy")]
+    group.bench_function("entity_per_pixel", |b| {
+        let mut sync = SyncBench::new();
+        b.iter(|| {
+            for frame in &frames {
+                sync.frame(*frame);
+            }
+        })
+    });
+    group.bench_function("bitmap", |b| {
+        let mut bitmap = vec![OFF; PIXELS];
+        b.iter(|| {
+            for frame in &frames {
+                frame.fill_bitmap(ON, OFF, &mut bitmap);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, throughput, screen);
+criterion_main!(benches);
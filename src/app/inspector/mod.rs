@@ -0,0 +1,185 @@
+// registers, stack, timers and keypad of the focused machine, editable while the clock is paused
+use bevy::prelude::{Query, Res, ResMut};
+use bevy_egui::{
+    egui::{self, Color32, RichText},
+    EguiContext,
+};
+
+use super::chip::{Cpu, CpuClock, Focus, Machine};
+use crate::chip::labels::LabelCache;
+
+const CHANGED: Color32 = Color32::from_rgb(0xFF, 0xC0, 0x40);
+const PRESSED: Color32 = Color32::from_rgb(0x20, 0x80, 0x20);
+const KEYPAD: [[usize; 4]; 4] = [
+    [0x1, 0x2, 0x3, 0xC],
+    [0x4, 0x5, 0x6, 0xD],
+    [0x7, 0x8, 0x9, 0xE],
+    [0xA, 0x0, 0xB, 0xF],
+];
+
+/// the values the panel can highlight, compared frame to frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Snapshot {
+    registers: [usize; 16],
+    i: usize,
+    counter: usize,
+    pointer: usize,
+    delay: usize,
+    sound: usize,
+}
+impl From<&Cpu> for Snapshot {
+    fn from(cpu: &Cpu) -> Self {
+        let mut registers = [0; 16];
+        registers.copy_from_slice(&cpu.registers[0..16]);
+        Snapshot {
+            registers,
+            i: cpu.i,
+            counter: cpu.counter,
+            pointer: cpu.pointer,
+            delay: cpu.delay,
+            sound: cpu.sound,
+        }
+    }
+}
+/// which of the snapshot values moved since the one before
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+struct Changed {
+    registers: [bool; 16],
+    i: bool,
+    counter: bool,
+    pointer: bool,
+    delay: bool,
+    sound: bool,
+}
+/// `changed` is only replaced when something moved, so a paused machine keeps
+/// showing what its last step touched
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Inspector {
+    last: Snapshot,
+    changed: Changed,
+    /// stack frame names, only rescanned when the focused machine's memory changes
+    labels: LabelCache,
+}
+impl Inspector {
+    fn observe(&mut self, now: Snapshot) {
+        if now == self.last {
+            return;
+        }
+        let mut changed = Changed::default();
+        for (i, (now, last)) in now
+            .registers
+            .iter()
+            .zip(self.last.registers.iter())
+            .enumerate()
+        {
+            changed.registers[i] = now != last;
+        }
+        changed.i = now.i != self.last.i;
+        changed.counter = now.counter != self.last.counter;
+        changed.pointer = now.pointer != self.last.pointer;
+        changed.delay = now.delay != self.last.delay;
+        changed.sound = now.sound != self.last.sound;
+        self.changed = changed;
+        self.last = now;
+    }
+}
+pub(crate) fn inspector_panel(
+    mut egui_context: ResMut<EguiContext>,
+    mut inspector: ResMut<Inspector>,
+    clock: Res<CpuClock>,
+    focus: Res<Focus>,
+    mut query: Query<(&Machine, &mut Cpu)>,
+) {
+    let mut cpu = match query.iter_mut().find(|(machine, _)| machine.0 == focus.0) {
+        Some((_, cpu)) => cpu,
+        None => return,
+    };
+    inspector.observe(Snapshot::from(&*cpu));
+    let changed = inspector.changed;
+    let labels = inspector.labels.get(&cpu.memory, cpu.entry());
+    let paused = clock.0.paused;
+    egui::SidePanel::left("inspector").show(egui_context.ctx_mut(), |ui| {
+        ui.heading("registers");
+        if !paused {
+            ui.label("(pause to edit)");
+        }
+        egui::Grid::new("registers").show(ui, |ui| {
+            for row in 0..8 {
+                for x in [row, row + 8] {
+                    register(
+                        ui,
+                        paused,
+                        &format!("V{:X}", x),
+                        &mut cpu.registers[x],
+                        0xFF,
+                        changed.registers[x],
+                    );
+                }
+                ui.end_row();
+            }
+            register(ui, paused, "PC", &mut cpu.counter, 0xFFF, changed.counter);
+            register(ui, paused, "I", &mut cpu.i, 0xFFF, changed.i);
+            ui.end_row();
+            register(ui, paused, "DT", &mut cpu.delay, 0xFF, changed.delay);
+            register(ui, paused, "ST", &mut cpu.sound, 0xFF, changed.sound);
+            ui.end_row();
+            let stack = cpu.stack.len();
+            register(ui, paused, "SP", &mut cpu.pointer, stack, changed.pointer);
+            ui.end_row();
+        });
+        ui.separator();
+        ui.heading("stack");
+        if cpu.pointer == 0 {
+            ui.label("(empty)");
+        }
+        for depth in (0..cpu.pointer.min(cpu.stack.len())).rev() {
+            let ret = cpu.stack[depth];
+            ui.monospace(format!("{:2} {:03X} {}", depth, ret, labels.resolve(ret)));
+        }
+        ui.monospace(format!(
+            "now {:03X} {}",
+            cpu.counter,
+            labels.resolve(cpu.counter)
+        ));
+        ui.separator();
+        ui.heading("keys");
+        egui::Grid::new("keypad").show(ui, |ui| {
+            for row in KEYPAD.iter() {
+                for &key in row.iter() {
+                    let mut text = RichText::new(format!(" {:X} ", key)).monospace();
+                    if cpu.keys[key] {
+                        text = text.background_color(PRESSED);
+                    }
+                    // clicking only does something while paused, running machines read the keyboard
+                    if ui
+                        .add(egui::Label::new(text).sense(egui::Sense::click()))
+                        .clicked()
+                        && paused
+                    {
+                        cpu.keys[key] = !cpu.keys[key];
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    });
+}
+fn register(
+    ui: &mut egui::Ui,
+    paused: bool,
+    name: &str,
+    value: &mut usize,
+    max: usize,
+    changed: bool,
+) {
+    let mut text = RichText::new(name).monospace();
+    if changed {
+        text = text.color(CHANGED);
+    }
+    ui.label(text);
+    if paused {
+        ui.add(egui::DragValue::new(value).clamp_range(0..=max));
+    } else {
+        ui.monospace(format!("{:02X}", value));
+    }
+}
@@ -1,11 +1,18 @@
 use bevy::{
     input::Input,
-    prelude::{KeyCode, Query, Res},
+    prelude::{KeyCode, Query, Res, ResMut},
 };
 
-use super::chip::Cpu;
-pub(crate) fn key_pressed(keys: Res<Input<KeyCode>>, mut query: Query<&mut Cpu>) {
-    for mut cpu in query.iter_mut() {
+use super::chip::{Cpu, CpuClock, Focus, Machine, Profiling};
+pub(crate) fn key_pressed(
+    keys: Res<Input<KeyCode>>,
+    focus: Res<Focus>,
+    mut query: Query<(&Machine, &mut Cpu)>,
+) {
+    for (machine, mut cpu) in query.iter_mut() {
+        if machine.0 != focus.0 {
+            continue;
+        }
         for key in keys.get_pressed() {
             match key {
                 KeyCode::Numpad1 => cpu.keys[1] = true,
@@ -38,9 +45,13 @@ pub(crate) fn key_pressed(keys: Res<Input<KeyCode>>, mut query: Query<&mut Cpu>)
 }
 pub(crate) fn key_just_released(
     keys: Res<Input<KeyCode>>,
-    mut query: Query<&mut Cpu>,
+    focus: Res<Focus>,
+    mut query: Query<(&Machine, &mut Cpu)>,
 ) {
-    for mut cpu in query.iter_mut() {
+    for (machine, mut cpu) in query.iter_mut() {
+        if machine.0 != focus.0 {
+            continue;
+        }
         for key in keys.get_just_released() {
             match key {
                 KeyCode::Numpad1 => cpu.keys[1] = false,
@@ -71,3 +82,52 @@ pub(crate) fn key_just_released(
         }
     }
 }
+/// F1-F4 presets, F5 pause, F6 frame advance, F7 slow motion, F8 turbo, F9 single step
+pub(crate) fn clock_keys(keys: Res<Input<KeyCode>>, mut clock: ResMut<CpuClock>) {
+    for key in keys.get_just_pressed() {
+        match key {
+            KeyCode::F1 => clock.0.preset(0),
+            KeyCode::F2 => clock.0.preset(1),
+            KeyCode::F3 => clock.0.preset(2),
+            KeyCode::F4 => clock.0.preset(3),
+            KeyCode::F5 => clock.0.toggle_pause(),
+            KeyCode::F6 => clock.0.advance(),
+            KeyCode::F7 => clock.0.toggle_slow(),
+            KeyCode::F8 => clock.0.toggle_turbo(),
+            KeyCode::F9 => clock.0.step(),
+            _ => (),
+        }
+    }
+}
+/// tab moves input to the next machine, whatever was held is let go
+pub(crate) fn focus_keys(
+    keys: Res<Input<KeyCode>>,
+    mut focus: ResMut<Focus>,
+    mut query: Query<&mut Cpu>,
+) {
+    if keys.just_pressed(KeyCode::Tab) {
+        focus.0 = (focus.0 + 1) % query.iter().count().max(1);
+        for mut cpu in query.iter_mut() {
+            cpu.keys = [false; 16];
+        }
+    }
+}
+/// F10 starts profiling the focused machine, again stops and prints the report
+pub(crate) fn profile_keys(
+    keys: Res<Input<KeyCode>>,
+    focus: Res<Focus>,
+    mut profiling: ResMut<Profiling>,
+    query: Query<(&Machine, &Cpu)>,
+) {
+    if !keys.just_pressed(KeyCode::F10) {
+        return;
+    }
+    match profiling.0 {
+        Some((profiled, _)) => {
+            if let Some((_, cpu)) = query.iter().find(|(machine, _)| machine.0 == profiled) {
+                profiling.stop(cpu);
+            }
+        }
+        None => profiling.start(focus.0),
+    }
+}
@@ -0,0 +1,133 @@
+// live hex view of the focused machine's memory, editable while the clock is paused
+use bevy::prelude::{Query, Res, ResMut};
+use bevy_egui::{
+    egui::{self, Color32, RichText},
+    EguiContext,
+};
+
+use super::chip::{Cpu, CpuClock, Focus, Machine};
+use crate::chip::opcode::Instruction;
+
+const ROW: usize = 16;
+const ROW_HEIGHT: f32 = 16.;
+const COUNTER: Color32 = Color32::from_rgb(0x40, 0x80, 0xFF);
+const INDEX: Color32 = Color32::from_rgb(0xE0, 0x80, 0x20);
+const SPRITE: Color32 = Color32::from_rgb(0x50, 0x50, 0x20);
+const SELECTED: Color32 = Color32::from_rgb(0x20, 0x80, 0x20);
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct MemoryView {
+    selected: Option<usize>,
+    edit: String,
+    sprite_rows: usize,
+}
+pub(crate) fn memory_panel(
+    mut egui_context: ResMut<EguiContext>,
+    mut view: ResMut<MemoryView>,
+    clock: Res<CpuClock>,
+    focus: Res<Focus>,
+    mut query: Query<(&Machine, &mut Cpu)>,
+) {
+    let mut cpu = match query.iter_mut().find(|(machine, _)| machine.0 == focus.0) {
+        Some((_, cpu)) => cpu,
+        None => return,
+    };
+    let paused = clock.0.paused;
+    let sprite = sprite_range(&cpu);
+    egui::SidePanel::right("memory").show(egui_context.ctx_mut(), |ui| {
+        ui.heading("memory");
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(format!("PC {:03X}", cpu.counter)).color(COUNTER));
+            ui.label(RichText::new(format!("I {:03X}", cpu.i)).color(INDEX));
+            if !paused {
+                ui.label("(pause to edit)");
+            }
+        });
+        if let Some(addr) = view.selected {
+            ui.horizontal(|ui| {
+                ui.monospace(format!("{:03X}:", addr));
+                let response = ui.add_enabled(
+                    paused,
+                    egui::TextEdit::singleline(&mut view.edit).desired_width(24.),
+                );
+                if response.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
+                    if let Ok(byte) = u8::from_str_radix(view.edit.trim(), 16) {
+                        cpu.memory[addr] = byte as usize;
+                        view.selected = Some((addr + 1) % cpu.memory.len());
+                        view.edit =
+                            format!("{:02X}", cpu.memory[(addr + 1) % cpu.memory.len()] as u8);
+                    }
+                }
+            });
+        }
+        ui.separator();
+        egui::ScrollArea::vertical()
+            .max_height(ui.available_height() / 2.)
+            .show_rows(ui, ROW_HEIGHT, cpu.memory.len() / ROW, |ui, rows| {
+                for row in rows {
+                    ui.horizontal(|ui| {
+                        ui.monospace(format!("{:03X}", row * ROW));
+                        for addr in row * ROW..(row + 1) * ROW {
+                            let mut text = RichText::new(format!("{:02X}", cpu.memory[addr] as u8))
+                                .monospace();
+                            text = match addr {
+                                _ if view.selected == Some(addr) => text.background_color(SELECTED),
+                                _ if addr == cpu.counter || addr == cpu.counter + 1 => {
+                                    text.background_color(COUNTER)
+                                }
+                                _ if addr == cpu.i => text.background_color(INDEX),
+                                _ if sprite.contains(&addr) => text.background_color(SPRITE),
+                                _ => text,
+                            };
+                            if ui
+                                .add(egui::Label::new(text).sense(egui::Sense::click()))
+                                .clicked()
+                            {
+                                view.selected = Some(addr);
+                                view.edit = format!("{:02X}", cpu.memory[addr] as u8);
+                            }
+                        }
+                    });
+                }
+            });
+        ui.separator();
+        sprite_preview(ui, &mut view, &cpu);
+    });
+}
+/// rows the next DRW will read, or just I when the next op is not a draw
+fn sprite_range(cpu: &Cpu) -> std::ops::Range<usize> {
+    match cpu.instruction_at(cpu.counter) {
+        Instruction::Drw(_, _, n) => cpu.i..cpu.i + n,
+        _ => cpu.i..cpu.i + 1,
+    }
+}
+/// the bytes at I drawn as an 8 wide sprite
+fn sprite_preview(ui: &mut egui::Ui, view: &mut MemoryView, cpu: &Cpu) {
+    if view.sprite_rows == 0 {
+        view.sprite_rows = sprite_range(cpu).len().max(1);
+    }
+    ui.horizontal(|ui| {
+        ui.label("sprite at I");
+        ui.add(egui::Slider::new(&mut view.sprite_rows, 1..=15).text("rows"));
+    });
+    let cell = 8.;
+    let (rect, _) = ui.allocate_exact_size(
+        egui::vec2(8. * cell, view.sprite_rows as f32 * cell),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0., Color32::BLACK);
+    for row in 0..view.sprite_rows {
+        let byte = cpu.memory.get(cpu.i + row).copied().unwrap_or(0);
+        for bit in 0..8 {
+            if (byte >> (7 - bit)) & 1 == 1 {
+                let min = rect.min + egui::vec2(bit as f32 * cell, row as f32 * cell);
+                painter.rect_filled(
+                    egui::Rect::from_min_size(min, egui::vec2(cell, cell)),
+                    0.,
+                    Color32::WHITE,
+                );
+            }
+        }
+    }
+}
@@ -1,11 +1,15 @@
 mod chip;
+mod inspector;
 mod keyboard;
+mod memory;
 mod screen;
+mod sprite;
 use bevy::{
     core::Timer,
-    prelude::{App, Color, Commands, Msaa, OrthographicCameraBundle, Transform},
+    prelude::{App, Color, Commands, Msaa, OrthographicCameraBundle, Res, Transform},
     DefaultPlugins,
 };
+use bevy_egui::EguiPlugin;
 use bevy_prototype_lyon::{
     plugin::ShapePlugin,
     prelude::{DrawMode, FillMode, GeometryBuilder, StrokeMode},
@@ -13,24 +17,45 @@ use bevy_prototype_lyon::{
 };
 use std::ops;
 
-use self::{chip::{cpu_cycle, CpuTimer, AppTimer, Cpu, default_registers, default_memory}, keyboard::{key_pressed, key_just_released}};
-pub(crate) fn new() {
+pub use self::screen::SyncBench;
+use self::{chip::{cpu_cycle, CpuClock, AppTimer, Cpu, Machine, Focus, Profiling}, keyboard::{key_pressed, key_just_released, clock_keys, focus_keys, profile_keys}};
+/// one rom per machine, no roms runs a single machine with the built in demo program
+pub(crate) struct Roms(pub(crate) Vec<Vec<u8>>);
+pub(crate) fn new(roms: Vec<Vec<u8>>) {
     App::new()
         .insert_resource(Msaa { samples: 4 })
+        .insert_resource(Roms(roms))
+        .insert_resource(Focus(0))
+        .init_resource::<Profiling>()
         .add_plugins(DefaultPlugins)
         .add_plugin(ShapePlugin)
+        .add_plugin(EguiPlugin)
+        .init_resource::<memory::MemoryView>()
+        .init_resource::<inspector::Inspector>()
+        .init_resource::<sprite::SpriteEditor>()
         .add_startup_system(setup_system)
-        .insert_resource(CpuTimer(Timer::from_seconds(0.1, true)))
+        .insert_resource(CpuClock(crate::chip::clock::Clock::default()))
         .insert_resource(AppTimer(Timer::from_seconds(1. / 60., true)))
         .add_system(screen::pixels_change_color)
         .add_system(screen::pixels_disable)
         .add_system(key_pressed)
         .add_system(key_just_released)
+        .add_system(clock_keys)
+        .add_system(focus_keys)
+        .add_system(profile_keys)
+        .add_system(screen::outline_focus)
         .add_system(cpu_cycle)
+        .add_system(screen::pixels_sync)
+        .add_system(memory::memory_panel)
+        .add_system(inspector::inspector_panel)
+        .add_system(sprite::sprite_panel)
         .run();
 }
-fn setup_system(mut commands: Commands) {
-    commands.spawn_bundle(OrthographicCameraBundle::new_2d());
+fn setup_system(mut commands: Commands, roms: Res<Roms>) {
+    let machines = roms.0.len().max(1);
+    let mut camera = OrthographicCameraBundle::new_2d();
+    camera.orthographic_projection.scale = screen::grid_columns(machines) as f32;
+    commands.spawn_bundle(camera);
 
     let shape = shapes::RegularPolygon {
         sides: 4,
@@ -38,34 +63,38 @@ fn setup_system(mut commands: Commands) {
         ..shapes::RegularPolygon::default()
     };
 
-    let empty = commands.spawn().id();
-    commands.entity(empty).despawn();
-    let mut screen = [screen::Pixel(empty, true); screen::PIXELS];
+    for machine in 0..machines {
+        let cpu = match roms.0.get(machine) {
+            Some(rom) => crate::chip::Cpu::load(rom),
+            None => crate::chip::Cpu::new(),
+        };
+        let empty = commands.spawn().id();
+        commands.entity(empty).despawn();
+        let mut screen = [screen::Pixel(empty, true); screen::PIXELS];
 
-    for i in 0..screen.len() {
-        screen[i].0 = commands
+        for i in 0..screen.len() {
+            screen[i].0 = commands
+                .spawn_bundle(GeometryBuilder::build_as(
+                    &shape,
+                    DrawMode::Outlined {
+                        fill_mode: FillMode::color(Color::CYAN),
+                        outline_mode: StrokeMode::new(Color::BLACK, 5.), // todo: export line width
+                    },
+                    ops::Fn::call(&Transform::from_xyz, screen::machine_i_to_xyz(machine, machines, i)),
+                ))
+                .id();
+        }
+        commands
             .spawn_bundle(GeometryBuilder::build_as(
-                &shape,
-                DrawMode::Outlined {
-                    fill_mode: FillMode::color(Color::CYAN),
-                    outline_mode: StrokeMode::new(Color::BLACK, 5.), // todo: export line width
-                },
-                ops::Fn::call(&Transform::from_xyz, screen::i_to_xyz(i)),
+                &screen::outline_shape(),
+                DrawMode::Stroke(StrokeMode::new(Color::DARK_GRAY, 2.)),
+                ops::Fn::call(&Transform::from_xyz, screen::machine_to_xyz(machine, machines)),
             ))
-            .id();
+            .insert(screen::Outline(machine));
+        commands
+            .spawn()
+            .insert(Cpu(cpu))
+            .insert(Machine(machine))
+            .insert(screen::Screen(screen));
     }
-    commands
-        .spawn()
-        .insert(Cpu {
-            registers: default_registers(),
-            stack: [0; usize::BITS as usize * 4],
-            memory: default_memory(),
-            counter: 0,
-            pointer: 0,
-            i: 0,
-            delay: 0,
-            sound: 0,
-            keys: [false; 16],
-        })
-        .insert(screen::Screen(screen));
 }
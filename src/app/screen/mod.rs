@@ -1,16 +1,23 @@
 use bevy::{
     core::Time,
-    prelude::{Added, Color, Commands, Component, Entity, Query, Res, Without},
+    ecs::schedule::{Schedule, Stage, SystemStage},
+    prelude::{Added, Color, Commands, Component, Entity, Query, Res, Without, World},
+};
+use bevy_prototype_lyon::{
+    prelude::{DrawMode, FillMode, StrokeMode},
+    shapes,
 };
-use bevy_prototype_lyon::prelude::DrawMode;
 use std::ops;
-pub(crate) const SCREEN_X: usize = 64;
-pub(crate) const SCREEN_Y: usize = 32;
-pub(crate) const PIXELS: usize = SCREEN_X * SCREEN_Y;
+
+pub(crate) use crate::chip::screen::{PIXELS, SCREEN_X, SCREEN_Y};
+
+use super::chip::{Cpu, Focus};
 const HALF_X: usize = SCREEN_X / 2;
 const HALF_Y: usize = SCREEN_Y / 2;
 const PIXEL_X: usize = 10;
 const PIXEL_Y: usize = 10;
+/// space between machines in the grid, in pixels
+const GAP: usize = 4;
 #[derive(Debug, Clone, Copy)]
 pub(crate) struct Pixel(pub(crate) Entity, pub(crate) bool);
 #[derive(Debug, Clone, Copy, Component)]
@@ -19,6 +26,36 @@ pub(crate) struct Screen(pub(crate) [Pixel; PIXELS]);
 pub(crate) struct Disabled();
 #[derive(Debug, Clone, Copy, Component)]
 pub(crate) struct Off();
+/// copies the headless framebuffer onto the pixel entities, only touching pixels that changed
+pub(crate) fn pixels_sync(mut commands: Commands, mut query: Query<(&Cpu, &mut Screen)>) {
+    for (cpu, mut screen) in query.iter_mut() {
+        for (pixel, bit) in screen.0.iter_mut().zip(cpu.screen.0.iter()) {
+            if pixel.1 != *bit {
+                pixel.set(&mut commands, *bit);
+            }
+        }
+    }
+}
+impl Pixel {
+    fn on(&mut self, commands: &mut Commands) {
+        commands.entity(self.0).remove::<Off>();
+        commands.entity(self.0).remove::<Disabled>();
+        self.1 = true;
+        // println!("{:?}", self);
+    }
+    fn off(&mut self, commands: &mut Commands) {
+        commands.entity(self.0).insert(Disabled());
+        self.1 = false;
+        // println!("{:?}", self);
+    }
+    fn set(&mut self, commands: &mut Commands, bit: bool) {
+        if bit {
+            self.on(commands);
+        } else {
+            self.off(commands);
+        }
+    }
+}
 pub(crate) fn pixels_disable(
     mut commands: Commands,
     mut query: Query<(Entity, &mut DrawMode), (Without<Off>, Added<Disabled>)>,
@@ -38,6 +75,59 @@ pub(crate) fn pixels_disable(
         commands.entity(id).insert(Off());
     }
 }
+/// one machine worth of pixel entities run through pixels_sync and pixels_disable without a
+/// window, what `cargo bench` measures against a bitmap framebuffer
+pub struct SyncBench {
+    world: World,
+    schedule: Schedule,
+    machine: Entity,
+}
+impl SyncBench {
+    pub fn new() -> SyncBench {
+        let mut world = World::new();
+        let empty = world.spawn().id();
+        world.despawn(empty);
+        let mut screen = [Pixel(empty, true); PIXELS];
+        for pixel in screen.iter_mut() {
+            pixel.0 = world
+                .spawn()
+                .insert(DrawMode::Outlined {
+                    fill_mode: FillMode::color(Color::CYAN),
+                    outline_mode: StrokeMode::new(Color::BLACK, 5.),
+                })
+                .id();
+        }
+        let machine = world
+            .spawn()
+            .insert(Cpu(crate::chip::Cpu::new()))
+            .insert(Screen(screen))
+            .id();
+        let mut schedule = Schedule::default();
+        schedule.add_stage(
+            "sync",
+            SystemStage::single_threaded()
+                .with_system(pixels_sync)
+                .with_system(pixels_disable),
+        );
+        SyncBench {
+            world,
+            schedule,
+            machine,
+        }
+    }
+    /// shows `frame` on the machine and syncs its pixels the way the app does every frame
+    pub fn frame(&mut self, frame: crate::chip::screen::Screen) {
+        if let Some(mut cpu) = self.world.get_mut::<Cpu>(self.machine) {
+            cpu.screen = frame;
+        }
+        self.schedule.run(&mut self.world);
+    }
+}
+impl Default for SyncBench {
+    fn default() -> Self {
+        SyncBench::new()
+    }
+}
 pub(crate) fn pixels_change_color(time: Res<Time>, mut query: Query<&mut DrawMode, Without<Off>>) {
     let hue = ((time.time_since_startup().as_millis() / 450) % 360) as f32;
     for mut draw_mode in query.iter_mut() {
@@ -56,6 +146,55 @@ pub(crate) fn i_to_xyz(i: usize) -> (f32, f32, f32) {
     let xyz: Xyz = i.into();
     xyz.into()
 }
+/// a pixel of one machine out of `machines`, grids are as square as they can be
+pub(crate) fn machine_i_to_xyz(machine: usize, machines: usize, i: usize) -> (f32, f32, f32) {
+    let (x, y, z) = i_to_xyz(i);
+    let (dx, dy, _) = machine_to_xyz(machine, machines);
+    (x + dx, y + dy, z)
+}
+/// center offset of a machine, the grid as a whole stays centered on the camera
+pub(crate) fn machine_to_xyz(machine: usize, machines: usize) -> (f32, f32, f32) {
+    let columns = grid_columns(machines);
+    let rows = machines.div_ceil(columns);
+    let (row, column) = div_rem(machine, columns);
+    let width = ((SCREEN_X + GAP) * PIXEL_X) as f32;
+    let height = ((SCREEN_Y + GAP) * PIXEL_Y) as f32;
+    (
+        (column as f32 - (columns - 1) as f32 / 2.) * width,
+        ((rows - 1) as f32 / 2. - row as f32) * height,
+        -1.,
+    )
+}
+pub(crate) fn grid_columns(machines: usize) -> usize {
+    (1..).find(|columns| columns * columns >= machines).unwrap()
+}
+/// frame around a machine, bright when it has the keyboard
+#[derive(Debug, Clone, Copy, Component)]
+pub(crate) struct Outline(pub(crate) usize);
+pub(crate) fn outline_shape() -> shapes::Rectangle {
+    shapes::Rectangle {
+        extents: bevy::math::Vec2::new(
+            ((SCREEN_X + GAP / 2) * PIXEL_X) as f32,
+            ((SCREEN_Y + GAP / 2) * PIXEL_Y) as f32,
+        ),
+        // pixels are centered on their index, nudge the frame by half a pixel to match
+        origin: shapes::RectangleOrigin::CustomCenter(bevy::math::Vec2::new(
+            -(PIXEL_X as f32) / 2.,
+            (PIXEL_Y as f32) / 2.,
+        )),
+    }
+}
+pub(crate) fn outline_focus(focus: Res<Focus>, mut query: Query<(&Outline, &mut DrawMode)>) {
+    for (outline, mut draw_mode) in query.iter_mut() {
+        if let DrawMode::Stroke(ref mut stroke_mode) = *draw_mode {
+            stroke_mode.color = if outline.0 == focus.0 {
+                Color::WHITE
+            } else {
+                Color::DARK_GRAY
+            };
+        }
+    }
+}
 #[derive(Debug, Clone, Copy)]
 struct Xyz {
     x: f32,
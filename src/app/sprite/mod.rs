@@ -0,0 +1,119 @@
+// click to toggle pixels, export the bytes for asm, hex or chiplox
+use bevy::prelude::{Query, Res, ResMut};
+use bevy_egui::{
+    egui::{self, Color32},
+    EguiContext,
+};
+
+use super::chip::{Cpu, Focus, Machine};
+use crate::chip::sprite::Sprite;
+
+const CELL: f32 = 16.;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Export {
+    Db,
+    Hex,
+    Chiplox,
+}
+#[derive(Debug, Clone)]
+pub(crate) struct SpriteEditor {
+    sprite: Sprite,
+    export: Export,
+}
+impl Default for SpriteEditor {
+    fn default() -> Self {
+        SpriteEditor {
+            sprite: Sprite::default(),
+            export: Export::Db,
+        }
+    }
+}
+pub(crate) fn sprite_panel(
+    mut egui_context: ResMut<EguiContext>,
+    mut editor: ResMut<SpriteEditor>,
+    focus: Res<Focus>,
+    query: Query<(&Machine, &Cpu)>,
+) {
+    let cpu = match query.iter().find(|(machine, _)| machine.0 == focus.0) {
+        Some((_, cpu)) => cpu,
+        None => return,
+    };
+    let editor = &mut *editor;
+    egui::Window::new("sprite").show(egui_context.ctx_mut(), |ui| {
+        let (mut width, mut rows) = (editor.sprite.width, editor.sprite.rows);
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut width, 8, "chip 8xN");
+            ui.radio_value(&mut width, 16, "schip 16x16");
+        });
+        if width == 16 {
+            rows = 16;
+        } else {
+            rows = rows.min(15);
+            ui.add(egui::Slider::new(&mut rows, 1..=15).text("rows"));
+        }
+        if (width, rows) != (editor.sprite.width, editor.sprite.rows) {
+            editor.sprite.resize(width, rows);
+        }
+        ui.horizontal(|ui| {
+            if ui.button("import from I").clicked() {
+                let len = editor.sprite.to_bytes().len();
+                let bytes: Vec<u8> = (cpu.i..cpu.i + len)
+                    .map(|addr| cpu.memory.get(addr).copied().unwrap_or(0) as u8)
+                    .collect();
+                editor.sprite = Sprite::from_bytes(width, &bytes);
+            }
+            if ui.button("clear").clicked() {
+                editor.sprite = Sprite::new(width, rows);
+            }
+        });
+        grid(ui, &mut editor.sprite);
+        ui.monospace(editor.sprite.to_hex());
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.radio_value(&mut editor.export, Export::Db, "db");
+            ui.radio_value(&mut editor.export, Export::Hex, "hex");
+            ui.radio_value(&mut editor.export, Export::Chiplox, "chiplox");
+        });
+        let mut text = match editor.export {
+            Export::Db => editor.sprite.to_db(),
+            Export::Hex => editor.sprite.to_hex(),
+            Export::Chiplox => editor.sprite.to_chiplox("sprite"),
+        };
+        if ui.button("copy").clicked() {
+            ui.output().copied_text = text.clone();
+        }
+        ui.add(
+            egui::TextEdit::multiline(&mut text)
+                .code_editor()
+                .interactive(false),
+        );
+    });
+}
+/// one cell per pixel, a click flips it
+fn grid(ui: &mut egui::Ui, sprite: &mut Sprite) {
+    let size = egui::vec2(sprite.width as f32 * CELL, sprite.rows as f32 * CELL);
+    let (rect, response) = ui.allocate_exact_size(size, egui::Sense::click());
+    if response.clicked() {
+        if let Some(pos) = response.interact_pointer_pos() {
+            let cell = (pos - rect.min) / CELL;
+            let (x, y) = (cell.x as usize, cell.y as usize);
+            if x < sprite.width && y < sprite.rows {
+                sprite.flp(x, y);
+            }
+        }
+    }
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0., Color32::BLACK);
+    for y in 0..sprite.rows {
+        for x in 0..sprite.width {
+            let min = rect.min + egui::vec2(x as f32 * CELL, y as f32 * CELL);
+            let cell = egui::Rect::from_min_size(min, egui::vec2(CELL, CELL));
+            if sprite.get(x, y) {
+                painter.rect_filled(cell.shrink(1.), 0., Color32::WHITE);
+            } else {
+                painter.rect_stroke(cell.shrink(1.), 0., (1., Color32::DARK_GRAY));
+            }
+        }
+    }
+}
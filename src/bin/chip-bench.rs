@@ -0,0 +1,74 @@
+#![feature(fn_traits)]
+#![feature(type_ascription)]
+#![feature(unboxed_closures)]
+#![feature(unwrap_infallible)]
+
+use std::{
+    fs, process,
+    time::{Duration, Instant},
+};
+
+use chiploxide::chip::{
+    clock::Tick,
+    dynarec::{divergence, Dynarec},
+    Cpu,
+};
+
+const USAGE: &str = "chip-bench <PATH_TO_CHIP8_FILE>... [--cycles <CYCLES>]";
+
+/// regression harness, every rom runs on both backends and has to end in the same state
+pub fn main() {
+    let mut roms = Vec::new();
+    let mut cycles = 1_000_000usize;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--cycles" => {
+                cycles = args
+                    .next()
+                    .and_then(|cycles| cycles.parse().ok())
+                    .unwrap_or_else(|| exit(USAGE))
+            }
+            _ => roms.push(arg),
+        }
+    }
+    if roms.is_empty() {
+        exit(USAGE);
+    }
+    let mut failed = 0;
+    for rom in &roms {
+        let cpu = Cpu::load(&fs::read(rom).unwrap_or_else(|e| exit(&format!("{}: {}", rom, e))));
+        let tick = Tick { cycles, timers: 0 };
+        let (interpreted, interpreter) = timed(cpu, |cpu| cpu.advance(tick));
+        let mut dynarec = Dynarec::new();
+        let (recompiled, recompiler) = timed(cpu, |cpu| dynarec.advance(cpu, tick));
+        println!(
+            "{}: interpreter {:?}, dynarec {:?} ({:.1}x), {} blocks",
+            rom,
+            interpreter,
+            recompiler,
+            interpreter.as_secs_f64() / recompiler.as_secs_f64().max(f64::EPSILON),
+            dynarec.cached()
+        );
+        if interpreted != recompiled {
+            failed += 1;
+            match divergence(&cpu, cycles) {
+                Some(cycle) => println!("  differs from cycle {}", cycle),
+                None => println!("  differs"),
+            }
+        }
+    }
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+/// final state and how long it took
+fn timed(mut cpu: Cpu, run: impl FnOnce(&mut Cpu)) -> (Cpu, Duration) {
+    let start = Instant::now();
+    run(&mut cpu);
+    (cpu, start.elapsed())
+}
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1)
+}
@@ -0,0 +1,35 @@
+#![feature(fn_traits)]
+#![feature(type_ascription)]
+#![feature(unboxed_closures)]
+#![feature(unwrap_infallible)]
+
+use std::{fs, process};
+
+use chiploxide::chip::{cfg::Cfg, Cpu};
+
+const USAGE: &str = "chip-cfg <PATH_TO_CHIP8_FILE> [--mermaid]";
+
+/// dot on stdout by default, `| dot -Tsvg > game.svg`
+pub fn main() {
+    let mut rom = None;
+    let mut mermaid = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--mermaid" => mermaid = true,
+            _ if rom.is_none() => rom = Some(arg),
+            _ => exit(USAGE),
+        }
+    }
+    let rom = rom.unwrap_or_else(|| exit(USAGE));
+    let cpu = Cpu::load(&fs::read(&rom).unwrap_or_else(|e| exit(&format!("{}: {}", rom, e))));
+    let cfg = Cfg::build(&cpu.memory, cpu.entry());
+    if mermaid {
+        print!("{}", cfg.to_mermaid());
+    } else {
+        print!("{}", cfg.to_dot());
+    }
+}
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1)
+}
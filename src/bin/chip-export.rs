@@ -0,0 +1,93 @@
+#![feature(fn_traits)]
+#![feature(type_ascription)]
+#![feature(unboxed_closures)]
+#![feature(unwrap_infallible)]
+
+use std::{env, fs, path::PathBuf, process};
+
+use chiploxide::chip::{
+    lint::lint,
+    payload::{Payload, DEFAULT_KEYMAP},
+};
+
+const USAGE: &str = "chip-export <PATH_TO_CHIP8_FILE> [-o <OUT_FILE>] [--quirks vip|schip|xochip] [--palette <RRGGBB>,<RRGGBB>] [--keys <16 KEYS FOR 0-F>] [--runner <CHIP_RUN>]";
+
+/// one file games, a copy of chip-run with the rom and settings appended
+pub fn main() {
+    let mut rom = None;
+    let mut out = None;
+    let mut profile = None;
+    let mut palette = Payload::default().palette;
+    let mut keymap = DEFAULT_KEYMAP;
+    let mut runner = None;
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().unwrap_or_else(|| exit(USAGE));
+        match arg.as_str() {
+            "-o" => out = Some(PathBuf::from(value())),
+            "--quirks" => profile = Some(value().parse().unwrap_or_else(|e: String| exit(&e))),
+            "--palette" => palette = parse_palette(&value()),
+            "--keys" => keymap = parse_keymap(&value()),
+            "--runner" => runner = Some(PathBuf::from(value())),
+            _ if rom.is_none() => rom = Some(PathBuf::from(arg)),
+            _ => exit(USAGE),
+        }
+    }
+    let rom = rom.unwrap_or_else(|| exit(USAGE));
+    let bytes = fs::read(&rom).unwrap_or_else(|e| exit(&format!("{}: {}", rom.display(), e)));
+    // whatever the linter would pick, unless told otherwise
+    let profile = profile.unwrap_or_else(|| lint(&bytes).profile);
+    // chip-run sits next to chip-export in target/ and in an install
+    let runner = runner.unwrap_or_else(|| {
+        env::current_exe()
+            .unwrap_or_else(|e| exit(&e.to_string()))
+            .with_file_name(format!("chip-run{}", env::consts::EXE_SUFFIX))
+    });
+    let template = fs::read(&runner).unwrap_or_else(|e| {
+        exit(&format!(
+            "{}: {} (build it with cargo build --bin chip-run)",
+            runner.display(),
+            e
+        ))
+    });
+    let out = out.unwrap_or_else(|| rom.with_extension(env::consts::EXE_EXTENSION));
+    let payload = Payload {
+        rom: bytes,
+        profile,
+        palette,
+        keymap,
+    };
+    fs::write(&out, payload.append(&template))
+        .unwrap_or_else(|e| exit(&format!("{}: {}", out.display(), e)));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = fs::set_permissions(&out, fs::Permissions::from_mode(0o755));
+    }
+    println!("{} ({} quirks)", out.display(), profile);
+}
+fn parse_palette(value: &str) -> (u32, u32) {
+    let colors: Vec<u32> = value
+        .split(',')
+        .map(|color| {
+            u32::from_str_radix(color.trim_start_matches('#'), 16).unwrap_or_else(|_| exit(USAGE))
+        })
+        .collect();
+    match colors[..] {
+        [on, off] => (on, off),
+        _ => exit(USAGE),
+    }
+}
+fn parse_keymap(value: &str) -> [char; 16] {
+    let keys: Vec<char> = value.chars().collect();
+    if keys.len() != 16 || !keys.iter().all(char::is_ascii_graphic) {
+        exit("--keys wants 16 printable ascii keys, for chip keys 0 to F");
+    }
+    let mut keymap = DEFAULT_KEYMAP;
+    keymap.copy_from_slice(&keys);
+    keymap
+}
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1)
+}
@@ -0,0 +1,35 @@
+#![feature(fn_traits)]
+#![feature(type_ascription)]
+#![feature(unboxed_closures)]
+#![feature(unwrap_infallible)]
+
+use std::{fs, process};
+
+use chiploxide::chip::lint::lint;
+
+const USAGE: &str = "chip-lint <PATH_TO_CHIP8_FILE>...";
+
+/// exits 1 if any rom has warnings, infos alone pass
+pub fn main() {
+    let roms: Vec<String> = std::env::args().skip(1).collect();
+    if roms.is_empty() {
+        exit(USAGE);
+    }
+    let mut warnings = 0;
+    for rom in &roms {
+        let bytes = fs::read(rom).unwrap_or_else(|e| exit(&format!("{}: {}", rom, e)));
+        let report = lint(&bytes);
+        if roms.len() > 1 {
+            println!("{}", rom);
+        }
+        print!("{}", report);
+        warnings += report.warnings();
+    }
+    if warnings > 0 {
+        process::exit(1);
+    }
+}
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1)
+}
@@ -0,0 +1,41 @@
+#![feature(fn_traits)]
+#![feature(type_ascription)]
+#![feature(unboxed_closures)]
+#![feature(unwrap_infallible)]
+
+use std::{fs, process};
+
+use chiploxide::chip::{labels::Labels, profile::Profiler, Cpu};
+
+const USAGE: &str = "chip-profile <PATH_TO_CHIP8_FILE> [CYCLES] [--folded <OUT_FILE>]";
+
+pub fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let mut rom = None;
+    let mut cycles = 100_000u64;
+    let mut folded = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--folded" => folded = args.next().cloned(),
+            _ if rom.is_none() => rom = Some(arg.clone()),
+            _ => cycles = arg.parse().unwrap_or_else(|_| exit(USAGE)),
+        }
+    }
+    let rom = rom.unwrap_or_else(|| exit(USAGE));
+    let mut cpu = Cpu::load(&fs::read(&rom).unwrap_or_else(|e| exit(&format!("{}: {}", rom, e))));
+    let mut profiler = Profiler::new();
+    for _ in 0..cycles {
+        profiler.run(&mut cpu);
+    }
+    let labels = Labels::scan(&cpu.memory, cpu.entry());
+    print!("{}", profiler.report(&cpu, &labels, 20));
+    if let Some(path) = folded {
+        fs::write(&path, profiler.folded(&labels))
+            .unwrap_or_else(|e| exit(&format!("{}: {}", path, e)));
+    }
+}
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1)
+}
@@ -0,0 +1,24 @@
+#![feature(fn_traits)]
+#![feature(type_ascription)]
+#![feature(unboxed_closures)]
+#![feature(unwrap_infallible)]
+
+use std::{env, fs, process};
+
+use chiploxide::{chip::payload::Payload, tui};
+
+/// template for chip-export, plays whatever payload is appended to this executable
+pub fn main() {
+    let exe = env::current_exe()
+        .and_then(fs::read)
+        .unwrap_or_else(|e| exit(&format!("can not read this executable: {}", e)));
+    match Payload::find(&exe) {
+        Some(Ok(payload)) => tui::play(&payload).unwrap_or_else(|e| exit(&e.to_string())),
+        Some(Err(e)) => exit(&e),
+        None => exit("nothing to play, this is the template chip-export appends a rom to"),
+    }
+}
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1)
+}
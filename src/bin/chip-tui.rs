@@ -0,0 +1,11 @@
+#![feature(fn_traits)]
+#![feature(type_ascription)]
+#![feature(unboxed_closures)]
+#![feature(unwrap_infallible)]
+
+use chiploxide::tui;
+
+pub fn main() -> std::io::Result<()> {
+    // chip-tui [PATH_TO_CHIP8_FILE], no path runs the built in demo program
+    tui::new(std::env::args().nth(1).as_deref())
+}
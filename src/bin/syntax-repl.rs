@@ -3,7 +3,7 @@
 #![feature(unboxed_closures)]
 #![feature(unwrap_infallible)]
 
-use chiploxide::lang::repl::tree::repl;
+use chiploxide::lang::repl::syntax::repl;
 
 #[macro_use]
 extern crate num_derive;
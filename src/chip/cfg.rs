@@ -0,0 +1,266 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+};
+
+use super::{
+    labels::Labels,
+    opcode::{Flow, Instruction},
+};
+
+/// why control moves from one block to the next
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Edge {
+    Next,     // falls through, including the skip not taken
+    Skip,     // 3/4/5/9/E ops jumping over the next instruction
+    Jump,     // 1NNN
+    Call,     // 2NNN, the return site gets a Next edge
+    Computed, // BNNN, target is NNN and V0 is added at run time
+}
+impl Edge {
+    fn name(&self) -> &'static str {
+        match self {
+            Edge::Next => "",
+            Edge::Skip => "skip",
+            Edge::Jump => "jump",
+            Edge::Call => "call",
+            Edge::Computed => "+ V0",
+        }
+    }
+}
+/// straight line run of instructions, only the last one can branch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub start: usize,
+    pub instructions: Vec<(usize, Instruction)>,
+    pub successors: Vec<(Edge, usize)>,
+}
+impl Block {
+    /// first byte after the block
+    pub fn end(&self) -> usize {
+        self.instructions
+            .last()
+            .map_or(self.start, |(addr, _)| addr + 2)
+    }
+}
+/// basic blocks reachable from an entry point, decoded without running anything
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Cfg {
+    pub entry: usize,
+    pub blocks: BTreeMap<usize, Block>,
+    /// 2NNN targets
+    pub subroutines: BTreeSet<usize>,
+    /// 1NNN targets
+    pub jumps: BTreeSet<usize>,
+}
+impl Cfg {
+    pub fn build(memory: &[usize], entry: usize) -> Cfg {
+        Cfg::build_with(memory, entry, |instruction| instruction.flow())
+    }
+    /// same walk with `flow` deciding where each instruction goes, the linter steps over
+    /// extension opcodes the interpreter would stop on
+    pub fn build_with(memory: &[usize], entry: usize, flow: impl Fn(Instruction) -> Flow) -> Cfg {
+        let decode = |addr: usize| {
+            Instruction::decode((memory[addr] as u8 as u16) << 8 | memory[addr + 1] as u8 as u16)
+        };
+        let in_memory = |addr: usize| addr + 1 < memory.len();
+        let mut cfg = Cfg {
+            entry,
+            ..Cfg::default()
+        };
+        let mut reachable = BTreeSet::new();
+        let mut leaders = BTreeSet::new();
+        leaders.insert(entry);
+        let mut work = vec![entry];
+        while let Some(addr) = work.pop() {
+            if !in_memory(addr) || !reachable.insert(addr) {
+                continue;
+            }
+            let step = flow(decode(addr));
+            let next = match step {
+                Flow::Next => vec![addr + 2],
+                Flow::Skip => vec![addr + 2, addr + 4],
+                Flow::Jump(target) => {
+                    cfg.jumps.insert(target);
+                    vec![target]
+                }
+                Flow::Call(target) => {
+                    cfg.subroutines.insert(target);
+                    vec![target, addr + 2]
+                }
+                Flow::Return | Flow::Computed(_) | Flow::Stop => vec![],
+            };
+            if step != Flow::Next {
+                leaders.extend(next.iter().copied());
+            }
+            work.extend(next);
+        }
+        for leader in leaders.iter().filter(|addr| reachable.contains(*addr)) {
+            let mut block = Block {
+                start: *leader,
+                instructions: Vec::new(),
+                successors: Vec::new(),
+            };
+            let mut addr = *leader;
+            loop {
+                let instruction = decode(addr);
+                block.instructions.push((addr, instruction));
+                let fall = addr + 2;
+                match flow(instruction) {
+                    Flow::Next if reachable.contains(&fall) && !leaders.contains(&fall) => {
+                        addr = fall;
+                        continue;
+                    }
+                    Flow::Next if in_memory(fall) => block.successors.push((Edge::Next, fall)),
+                    Flow::Skip => block
+                        .successors
+                        .extend([(Edge::Next, fall), (Edge::Skip, addr + 4)]),
+                    Flow::Jump(target) => block.successors.push((Edge::Jump, target)),
+                    Flow::Call(target) => block
+                        .successors
+                        .extend([(Edge::Call, target), (Edge::Next, fall)]),
+                    Flow::Computed(target) => block.successors.push((Edge::Computed, target)),
+                    _ => (),
+                }
+                block.successors.retain(|(_, target)| in_memory(*target));
+                break;
+            }
+            cfg.blocks.insert(*leader, block);
+        }
+        cfg
+    }
+    /// start for the entry, sub_XXX for 2NNN targets, loc_XXX for 1NNN targets
+    pub fn labels(&self) -> Labels {
+        let mut labels = BTreeMap::new();
+        for target in &self.jumps {
+            labels.insert(*target, format!("loc_{:03X}", target));
+        }
+        for target in &self.subroutines {
+            labels.insert(*target, format!("sub_{:03X}", target));
+        }
+        labels.insert(self.entry, "start".to_string());
+        Labels(labels)
+    }
+    /// graphviz, one box per block with its disassembly left aligned
+    pub fn to_dot(&self) -> String {
+        let labels = self.labels();
+        let mut out = String::from("digraph cfg {\n    node [shape=box fontname=\"monospace\"];\n");
+        for block in self.blocks.values() {
+            let mut text = format!("{}:\\l", labels.resolve(block.start));
+            for (addr, instruction) in &block.instructions {
+                let _ = write!(text, "{:03X}  {}\\l", addr, instruction);
+            }
+            let _ = writeln!(
+                out,
+                "    b{:03X} [label=\"{}\"];",
+                block.start,
+                text.replace('"', "\\\"")
+            );
+        }
+        for block in self.blocks.values() {
+            for (edge, target) in &block.successors {
+                match edge {
+                    Edge::Computed => {
+                        let _ = writeln!(
+                            out,
+                            "    u{0:03X} [label=\"{1:03X} + V0\" shape=ellipse style=dashed];\n    b{0:03X} -> u{0:03X} [style=dashed];",
+                            block.start, target
+                        );
+                    }
+                    Edge::Next => {
+                        let _ = writeln!(out, "    b{:03X} -> b{:03X};", block.start, target);
+                    }
+                    _ => {
+                        let _ = writeln!(
+                            out,
+                            "    b{:03X} -> b{:03X} [label=\"{}\"];",
+                            block.start,
+                            target,
+                            edge.name()
+                        );
+                    }
+                }
+            }
+        }
+        out.push_str("}\n");
+        out
+    }
+    /// same graph for markdown renderers, unknown BNNN targets are dotted circles
+    pub fn to_mermaid(&self) -> String {
+        let labels = self.labels();
+        let mut out = String::from("flowchart TD\n");
+        for block in self.blocks.values() {
+            let mut text = format!("{}:", labels.resolve(block.start));
+            for (addr, instruction) in &block.instructions {
+                let _ = write!(text, "<br/>{:03X} {}", addr, instruction);
+            }
+            let _ = writeln!(
+                out,
+                "    b{:03X}[\"{}\"]",
+                block.start,
+                text.replace('"', "#quot;")
+            );
+        }
+        for block in self.blocks.values() {
+            for (edge, target) in &block.successors {
+                let _ = match edge {
+                    Edge::Computed => writeln!(
+                        out,
+                        "    b{0:03X} -.-> u{0:03X}((\"{1:03X} + V0\"))",
+                        block.start, target
+                    ),
+                    Edge::Next => writeln!(out, "    b{:03X} --> b{:03X}", block.start, target),
+                    _ => writeln!(
+                        out,
+                        "    b{:03X} -->|{}| b{:03X}",
+                        block.start,
+                        edge.name(),
+                        target
+                    ),
+                };
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip::Cpu;
+
+    #[test]
+    fn splits_blocks_at_branches() {
+        let cpu = Cpu::load(&[
+            0x60, 0x01, // 200: LD   V0, 01
+            0x30, 0x01, // 202: SE   V0, 01
+            0x22, 0x0A, // 204: CALL 20A
+            0x12, 0x00, // 206: JP   200
+            0xB3, 0x00, // 208: JP   V0, 300
+            0x00, 0xEE, // 20A: RET
+        ]);
+        let cfg = Cfg::build(&cpu.memory, cpu.entry());
+        let starts: Vec<usize> = cfg.blocks.keys().copied().collect();
+        assert_eq!(starts, vec![0x200, 0x204, 0x206, 0x20A]);
+        assert_eq!(cfg.blocks[&0x200].instructions.len(), 2);
+        assert_eq!(
+            cfg.blocks[&0x200].successors,
+            vec![(Edge::Next, 0x204), (Edge::Skip, 0x206)]
+        );
+        assert_eq!(
+            cfg.blocks[&0x204].successors,
+            vec![(Edge::Call, 0x20A), (Edge::Next, 0x206)]
+        );
+        assert_eq!(cfg.labels().resolve(0x20A), "sub_20A");
+        assert!(cfg.to_dot().contains("b200 -> b206 [label=\"skip\"];"));
+        assert!(cfg.to_mermaid().contains("b204 -->|call| b20A"));
+    }
+
+    #[test]
+    fn computed_jump_is_unknown() {
+        let cpu = Cpu::load(&[0xB3, 0x00]);
+        let cfg = Cfg::build(&cpu.memory, cpu.entry());
+        assert_eq!(cfg.blocks[&0x200].successors, vec![(Edge::Computed, 0x300)]);
+        assert!(cfg.to_dot().contains("u200 [label=\"300 + V0\""));
+    }
+}
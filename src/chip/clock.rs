@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+/// instructions per second presets, hotkeys pick one
+pub const PRESETS: [u32; 4] = [500, 600, 700, 1000];
+/// delay and sound count down at 60hz no matter how fast the cpu goes
+pub const TIMER_HZ: u32 = 60;
+/// never owe more than this much time, a long frame (window drag, breakpoint, slow
+/// terminal) drops the extra instead of running thousands of ops to catch up
+const MAX_CATCH_UP: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Speed {
+    Slow,
+    Normal,
+    Turbo,
+}
+impl Speed {
+    fn scale(self, delta: Duration) -> Duration {
+        match self {
+            Speed::Slow => delta / 8,
+            Speed::Normal => delta,
+            Speed::Turbo => delta * 8,
+        }
+    }
+}
+/// how much work one frame gets
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Tick {
+    pub cycles: usize,
+    pub timers: usize,
+}
+/// turns frame time into instructions, frontends own one and hand each Tick to Cpu::advance
+#[derive(Debug, Clone, Copy)]
+pub struct Clock {
+    pub hz: u32,
+    pub speed: Speed,
+    pub paused: bool,
+    advance: usize,
+    steps: usize,
+    owed: Duration,
+    timer_owed: Duration,
+}
+impl Default for Clock {
+    fn default() -> Self {
+        Clock::new(PRESETS[0])
+    }
+}
+impl Clock {
+    pub fn new(hz: u32) -> Clock {
+        Clock {
+            hz: hz.max(1),
+            speed: Speed::Normal,
+            paused: false,
+            advance: 0,
+            steps: 0,
+            owed: Duration::ZERO,
+            timer_owed: Duration::ZERO,
+        }
+    }
+    pub fn tick(&mut self, delta: Duration) -> Tick {
+        if self.paused {
+            if self.advance > 0 {
+                self.advance -= 1;
+                return Tick {
+                    cycles: self.cycles_per_frame(),
+                    timers: 1,
+                };
+            }
+            if self.steps > 0 {
+                self.steps -= 1;
+                return Tick {
+                    cycles: 1,
+                    timers: 0,
+                };
+            }
+            return Tick::default();
+        }
+        // speed only scales the cpu, timers stay on wall time
+        let scaled = self.speed.scale(delta);
+        self.owed = (self.owed + scaled).min(self.speed.scale(MAX_CATCH_UP));
+        self.timer_owed = (self.timer_owed + delta).min(MAX_CATCH_UP);
+        Tick {
+            cycles: Clock::take(&mut self.owed, self.hz),
+            timers: Clock::take(&mut self.timer_owed, TIMER_HZ),
+        }
+    }
+    /// whole periods of `hz` that fit in `owed`, the remainder is kept for next frame
+    fn take(owed: &mut Duration, hz: u32) -> usize {
+        let period = 1_000_000_000 / hz as u128;
+        let periods = owed.as_nanos() / period;
+        *owed -= Duration::from_nanos((periods * period) as u64);
+        periods as usize
+    }
+    pub fn cycles_per_frame(&self) -> usize {
+        self.hz.div_ceil(TIMER_HZ) as usize
+    }
+    /// same knob as hz, counted the way most emulators expose it
+    pub fn set_cycles_per_frame(&mut self, cycles: usize) {
+        self.hz = (cycles.max(1) as u32) * TIMER_HZ;
+    }
+    pub fn preset(&mut self, i: usize) {
+        if let Some(hz) = PRESETS.get(i) {
+            self.hz = *hz;
+        }
+    }
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.advance = 0;
+        self.steps = 0;
+        self.owed = Duration::ZERO;
+        self.timer_owed = Duration::ZERO;
+    }
+    /// one frame worth of work on the next tick, only while paused
+    pub fn advance(&mut self) {
+        if self.paused {
+            self.advance += 1;
+        }
+    }
+    /// a single instruction on the next tick, only while paused
+    pub fn step(&mut self) {
+        if self.paused {
+            self.steps += 1;
+        }
+    }
+    pub fn toggle_turbo(&mut self) {
+        self.speed = match self.speed {
+            Speed::Turbo => Speed::Normal,
+            _ => Speed::Turbo,
+        };
+    }
+    pub fn toggle_slow(&mut self) {
+        self.speed = match self.speed {
+            Speed::Slow => Speed::Normal,
+            _ => Speed::Slow,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frame_runs_hz_over_sixty() {
+        let mut clock = Clock::new(600);
+        let tick = clock.tick(Duration::from_millis(100));
+        assert_eq!(
+            tick,
+            Tick {
+                cycles: 60,
+                timers: 6
+            }
+        );
+    }
+
+    #[test]
+    fn remainder_carries_over() {
+        let mut clock = Clock::new(500);
+        let cycles: usize = (0..60)
+            .map(|_| clock.tick(Duration::from_nanos(1_000_000_000 / 60)).cycles)
+            .sum();
+        assert!((499..=500).contains(&cycles));
+    }
+
+    #[test]
+    fn slow_frame_is_bounded() {
+        let mut clock = Clock::new(1000);
+        let tick = clock.tick(Duration::from_secs(5));
+        assert_eq!(tick.cycles, 100);
+        assert_eq!(clock.tick(Duration::ZERO).cycles, 0);
+    }
+
+    #[test]
+    fn turbo_keeps_timers_at_sixty() {
+        let mut clock = Clock::new(600);
+        clock.toggle_turbo();
+        let ticks: Vec<Tick> = (0..60)
+            .map(|_| clock.tick(Duration::from_nanos(1_000_000_000 / 60)))
+            .collect();
+        let cycles: usize = ticks.iter().map(|tick| tick.cycles).sum();
+        let timers: usize = ticks.iter().map(|tick| tick.timers).sum();
+        assert!((4799..=4800).contains(&cycles));
+        assert!((59..=60).contains(&timers));
+    }
+
+    #[test]
+    fn paused_only_moves_on_advance() {
+        let mut clock = Clock::new(600);
+        clock.toggle_pause();
+        assert_eq!(clock.tick(Duration::from_millis(100)), Tick::default());
+        clock.advance();
+        assert_eq!(
+            clock.tick(Duration::ZERO),
+            Tick {
+                cycles: 10,
+                timers: 1
+            }
+        );
+        assert_eq!(clock.tick(Duration::ZERO), Tick::default());
+        clock.step();
+        assert_eq!(
+            clock.tick(Duration::ZERO),
+            Tick {
+                cycles: 1,
+                timers: 0
+            }
+        );
+    }
+}
@@ -0,0 +1,270 @@
+// cached block backend, decodes each straight line run once and replays it as closures
+// every closure does what Cpu::run does for that opcode, so the two backends stay interchangeable
+use std::rc::Rc;
+
+use super::{
+    clock::Tick,
+    opcode::{Flow, Instruction},
+    Cpu,
+};
+
+/// longest block, bounds how much a single miss decodes
+const MAX_BLOCK: usize = 64;
+
+type Op = Box<dyn Fn(&mut Cpu)>;
+
+/// counter + 2, then the op itself
+macro_rules! op {
+    (|$cpu:ident| $body:expr) => {
+        Box::new(move |$cpu: &mut Cpu| {
+            $cpu.counter += 2;
+            $body;
+        })
+    };
+}
+
+struct Block {
+    /// first byte after the last instruction
+    end: usize,
+    ops: Vec<Op>,
+    /// bytes the last op writes at I, FX55 and FX33 end a block so the rest can be invalidated
+    writes: Option<usize>,
+}
+/// drop in for Cpu::advance, blocks are keyed by start address
+pub struct Dynarec {
+    blocks: Vec<Option<Rc<Block>>>,
+}
+impl Default for Dynarec {
+    fn default() -> Self {
+        Dynarec::new()
+    }
+}
+impl Dynarec {
+    pub fn new() -> Dynarec {
+        Dynarec {
+            blocks: vec![None; 4096],
+        }
+    }
+    /// same contract as Cpu::advance
+    pub fn advance(&mut self, cpu: &mut Cpu, tick: Tick) {
+        let mut cycles = tick.cycles;
+        while cycles > 0 {
+            cycles -= self.run(cpu, cycles);
+        }
+        for _ in 0..tick.timers {
+            cpu.tick_timers();
+        }
+    }
+    /// up to `budget` ops of the block at the counter, returns how many ran
+    pub fn run(&mut self, cpu: &mut Cpu, budget: usize) -> usize {
+        let start = cpu.counter;
+        if start + 1 >= cpu.memory.len() {
+            // the counter wraps at the end of memory, let the interpreter do that the same way
+            cpu.run();
+            return 1;
+        }
+        let block = match &self.blocks[start] {
+            Some(block) => block.clone(),
+            None => {
+                let block = Rc::new(compile(cpu, start));
+                self.blocks[start] = Some(block.clone());
+                block
+            }
+        };
+        let ran = block.ops.len().min(budget);
+        let (last, body) = block.ops[..ran]
+            .split_last()
+            .expect("blocks are never empty");
+        for op in body {
+            op(cpu);
+        }
+        let i = cpu.i;
+        last(cpu);
+        if let (Some(len), true) = (block.writes, ran == block.ops.len()) {
+            // the writes wrap at the end of memory, so the stale blocks can be at both ends
+            let (start, end) = (i & 0xFFF, (i & 0xFFF) + len);
+            let size = self.blocks.len();
+            self.invalidate(start, end.min(size));
+            if end > size {
+                self.invalidate(0, end - size);
+            }
+        }
+        ran
+    }
+    /// drops every block with a byte in `start..end`
+    pub fn invalidate(&mut self, start: usize, end: usize) {
+        let first = start.saturating_sub(MAX_BLOCK * 2);
+        for slot in self.blocks.iter_mut().take(end).skip(first) {
+            if let Some(block) = slot {
+                if block.end > start {
+                    *slot = None;
+                }
+            }
+        }
+    }
+    /// call after poking memory from outside the cpu, an editor or a new rom
+    pub fn flush(&mut self) {
+        self.blocks.iter_mut().for_each(|slot| *slot = None);
+    }
+    pub fn cached(&self) -> usize {
+        self.blocks.iter().filter(|slot| slot.is_some()).count()
+    }
+}
+/// straight line from `start` up to the first op that can move the counter or write memory
+fn compile(cpu: &Cpu, start: usize) -> Block {
+    let mut ops = Vec::new();
+    let mut addr = start;
+    let writes = loop {
+        let instruction = cpu.instruction_at(addr);
+        ops.push(translate(instruction));
+        addr += 2;
+        let writes = match instruction {
+            Instruction::LdIV(x) => Some(x + 1),
+            Instruction::LdB(_) => Some(3),
+            _ => None,
+        };
+        // FX0A waits by staying on itself, which the rest of the block must not run past
+        let ends = instruction.flow() != Flow::Next
+            || matches!(instruction, Instruction::LdKey(_))
+            || writes.is_some()
+            || ops.len() == MAX_BLOCK
+            || addr + 1 >= cpu.memory.len();
+        if ends {
+            break writes;
+        }
+    };
+    Block {
+        end: addr,
+        ops,
+        writes,
+    }
+}
+/// mirrors Cpu::execute arm for arm
+fn translate(instruction: Instruction) -> Op {
+    match instruction {
+        Instruction::Cls => op!(|cpu| cpu.screen.clr()),
+        Instruction::Ret => op!(|cpu| cpu.ret()),
+        Instruction::Jp(addr) => op!(|cpu| cpu.jp(addr)),
+        Instruction::Call(addr) => op!(|cpu| cpu.call(addr)),
+        Instruction::SeK(x, kk) => op!(|cpu| cpu.skp(cpu.registers[x] == kk)),
+        Instruction::SneK(x, kk) => op!(|cpu| cpu.skp(cpu.registers[x] != kk)),
+        Instruction::Se(x, y) => op!(|cpu| cpu.skp(cpu.registers[x] == cpu.registers[y])),
+        Instruction::LdK(x, kk) => op!(|cpu| cpu.ldk(x, kk)),
+        Instruction::AddK(x, kk) => op!(|cpu| cpu.addk(x, kk)),
+        Instruction::Ld(x, y) => op!(|cpu| cpu.ld(x, y)),
+        Instruction::Or(x, y) => op!(|cpu| cpu.or(x, y)),
+        Instruction::And(x, y) => op!(|cpu| cpu.and(x, y)),
+        Instruction::Xor(x, y) => op!(|cpu| cpu.xor(x, y)),
+        Instruction::Add(x, y) => op!(|cpu| cpu.add(x, y)),
+        Instruction::Sub(x, y) => op!(|cpu| cpu.sub(x, y)),
+        Instruction::Shr(x, y) => op!(|cpu| cpu.shr(x, y)),
+        Instruction::Subn(x, y) => op!(|cpu| cpu.subn(x, y)),
+        Instruction::Shl(x, y) => op!(|cpu| cpu.shl(x, y)),
+        Instruction::Sne(x, y) => op!(|cpu| cpu.skp(cpu.registers[x] != cpu.registers[y])),
+        Instruction::LdI(addr) => op!(|cpu| cpu.ldik(addr)),
+        Instruction::Drw(x, y, n) => op!(|cpu| cpu.drw(x, y, n)),
+        // rare ones, the interpreter keeps them in one place
+        _ => op!(|cpu| cpu.execute(instruction)),
+    }
+}
+/// runs both backends one op at a time, the first cycle where they disagree
+pub fn divergence(cpu: &Cpu, cycles: usize) -> Option<usize> {
+    let mut interpreted = *cpu;
+    let mut recompiled = *cpu;
+    let mut dynarec = Dynarec::new();
+    for cycle in 0..cycles {
+        interpreted.run();
+        dynarec.run(&mut recompiled, 1);
+        if interpreted != recompiled {
+            return Some(cycle);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_interpreter() {
+        assert_eq!(divergence(&Cpu::new(), 500), None);
+        let rom = [
+            0x00, 0xE0, // 200: CLS
+            0x60, 0x05, // 202: LD   V0, 05
+            0x61, 0x00, // 204: LD   V1, 00
+            0x71, 0x01, // 206: ADD  V1, 01
+            0x81, 0x04, // 208: ADD  V1, V0
+            0x51, 0x00, // 20A: SE   V1, V0
+            0x22, 0x12, // 20C: CALL 212
+            0x12, 0x06, // 20E: JP   206
+            0x00, 0x00, // 210:
+            0xA2, 0x00, // 212: LD   I, 200
+            0xD0, 0x15, // 214: DRW  V0, V1, 5
+            0x00, 0xEE, // 216: RET
+        ];
+        assert_eq!(divergence(&Cpu::load(&rom), 2000), None);
+    }
+
+    #[test]
+    fn self_modifying_code() {
+        let rom = [
+            0x22, 0x10, // 200: CALL 210, compiles the block at 210
+            0xA2, 0x10, // 202: LD   I, 210
+            0x60, 0x6E, // 204: LD   V0, 6E
+            0x61, 0x02, // 206: LD   V1, 02
+            0xF1, 0x55, // 208: LD   [I], V1, 210 is now LD VE, 02
+            0x22, 0x10, // 20A: CALL 210
+            0x12, 0x0C, // 20C: JP   20C
+            0x00, 0x00, // 20E:
+            0x6E, 0x01, // 210: LD   VE, 01
+            0x00, 0xEE, // 212: RET
+        ];
+        let wrapped = [
+            0x20, 0x00, // 200: CALL 000, compiles the block at 000
+            0xAF, 0xFF, // 202: LD   I, FFF
+            0x61, 0x6E, // 204: LD   V1, 6E
+            0x62, 0x02, // 206: LD   V2, 02
+            0xF2, 0x55, // 208: LD   [I], V2, wraps so 000 is now LD VE, 02
+            0x20, 0x00, // 20A: CALL 000
+            0x12, 0x0C, // 20C: JP   20C
+        ];
+        let mut low = Cpu::load(&wrapped);
+        low.memory[..4].copy_from_slice(&[0x6E, 0x01, 0x00, 0xEE]);
+        for cpu in [Cpu::load(&rom), low] {
+            assert_eq!(divergence(&cpu, 100), None);
+            // whole blocks at a time, the FX55 has to drop the stale block it wrote over
+            let mut interpreted = cpu;
+            let mut recompiled = cpu;
+            let mut dynarec = Dynarec::new();
+            let tick = Tick {
+                cycles: 100,
+                timers: 0,
+            };
+            interpreted.advance(tick);
+            dynarec.advance(&mut recompiled, tick);
+            assert_eq!(recompiled.registers[0xE], 2);
+            assert_eq!(interpreted, recompiled);
+        }
+    }
+
+    #[test]
+    fn budget_splits_blocks() {
+        let rom = [0x70, 0x01, 0x70, 0x01, 0x70, 0x01, 0x12, 0x00];
+        let mut interpreted = Cpu::load(&rom);
+        let mut recompiled = interpreted;
+        let mut dynarec = Dynarec::new();
+        let tick = Tick {
+            cycles: 3,
+            timers: 1,
+        };
+        for _ in 0..5 {
+            interpreted.advance(tick);
+            dynarec.advance(&mut recompiled, tick);
+            assert_eq!(interpreted, recompiled);
+        }
+        assert_eq!(dynarec.cached(), 4);
+        dynarec.invalidate(0x204, 0x206);
+        assert_eq!(dynarec.cached(), 1);
+    }
+}
@@ -0,0 +1,60 @@
+use std::collections::BTreeMap;
+
+use super::cfg::Cfg;
+
+/// names for the addresses a program jumps or calls to, so stack frames and
+/// disassembly can say `sub_2A0+6` instead of a bare `2A6`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Labels(pub BTreeMap<usize, String>);
+impl Labels {
+    /// only what is reachable from `entry` gets a name, see Cfg::labels
+    pub fn scan(memory: &[usize], entry: usize) -> Labels {
+        Cfg::build(memory, entry).labels()
+    }
+    /// nearest label at or before `addr`, with the distance past it
+    pub fn resolve(&self, addr: usize) -> String {
+        match self.0.range(..=addr).next_back() {
+            Some((label_addr, label)) if *label_addr == addr => label.clone(),
+            Some((label_addr, label)) => format!("{}+{}", label, addr - label_addr),
+            None => format!("{:03X}", addr),
+        }
+    }
+}
+/// labels of one memory image, scanned again only when the memory or the entry changes,
+/// for views that want names every frame
+#[derive(Debug, Clone, Default)]
+pub struct LabelCache {
+    memory: Vec<usize>,
+    entry: usize,
+    labels: Labels,
+}
+impl LabelCache {
+    pub fn get(&mut self, memory: &[usize], entry: usize) -> &Labels {
+        if self.memory != memory || self.entry != entry {
+            self.labels = Labels::scan(memory, entry);
+            self.memory = memory.to_vec();
+            self.entry = entry;
+        }
+        &self.labels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chip::Cpu;
+
+    #[test]
+    fn cache_follows_memory() {
+        let mut cpu = Cpu::load(&[
+            0x22, 0x04, // 200: CALL 204
+            0x12, 0x02, // 202: JP   202
+            0x00, 0xEE, // 204: RET
+        ]);
+        let mut cache = LabelCache::default();
+        assert_eq!(cache.get(&cpu.memory, 0x200).resolve(0x204), "sub_204");
+        cpu.memory[0x201] = 0x06;
+        assert_eq!(cache.get(&cpu.memory, 0x200).resolve(0x206), "sub_206");
+        assert_eq!(cache.get(&cpu.memory, 0x200).resolve(0x204), "loc_202+2");
+    }
+}
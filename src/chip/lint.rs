@@ -0,0 +1,664 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+};
+
+use super::{
+    cfg::{Cfg, Edge},
+    opcode::{Flow, Instruction},
+    quirks::Profile,
+    Cpu, PROGRAM_START,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Lint {
+    Unreachable,   // bytes nothing jumps to or points I at
+    OddJump,       // 1NNN/2NNN/BNNN to an odd address or into the middle of an instruction
+    Uninitialized, // register read before any path writes it
+    MachineCall,   // 0NNN, only a real cosmac vip can run these
+    SelfModifying, // FX55/FX33 writing over reachable code
+    MissingReturn, // 2NNN to a subroutine that never reaches 00EE
+    Quirk,         // means something different depending on the interpreter
+}
+impl Lint {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::Unreachable => "unreachable",
+            Lint::OddJump => "odd-jump",
+            Lint::Uninitialized => "uninitialized",
+            Lint::MachineCall => "machine-call",
+            Lint::SelfModifying => "self-modifying",
+            Lint::MissingReturn => "missing-return",
+            Lint::Quirk => "quirk",
+        }
+    }
+    /// unreachable bytes are usually sprite data reached through I + VX, quirks are a fact of life
+    pub fn severity(&self) -> Severity {
+        match self {
+            Lint::Unreachable | Lint::Quirk => Severity::Info,
+            _ => Severity::Warning,
+        }
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Finding {
+    pub addr: usize,
+    pub lint: Lint,
+    pub message: String,
+}
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.lint.severity() {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+        };
+        write!(
+            f,
+            "{}[{}] {:03X}: {}",
+            severity,
+            self.lint.name(),
+            self.addr,
+            self.message
+        )
+    }
+}
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub findings: Vec<Finding>,
+    /// quirk profile the rom most likely expects
+    pub profile: Profile,
+    /// why that profile was picked
+    pub reasons: Vec<String>,
+}
+impl Report {
+    pub fn warnings(&self) -> usize {
+        self.findings
+            .iter()
+            .filter(|finding| finding.lint.severity() == Severity::Warning)
+            .count()
+    }
+}
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for finding in &self.findings {
+            writeln!(f, "{}", finding)?;
+        }
+        writeln!(f, "recommended quirks: {}", self.profile)?;
+        for reason in &self.reasons {
+            writeln!(f, "  {}", reason)?;
+        }
+        Ok(())
+    }
+}
+/// what is known before an instruction runs, on every path that gets there
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct State {
+    /// bit per register written on every path
+    written: u16,
+    /// I if every path agrees on it
+    i: Option<usize>,
+}
+impl State {
+    fn meet(self, other: State) -> State {
+        State {
+            written: self.written & other.written,
+            i: if self.i == other.i { self.i } else { None },
+        }
+    }
+    fn after(self, instruction: Instruction) -> State {
+        State {
+            written: self.written | writes(instruction),
+            i: match instruction {
+                Instruction::LdI(addr) => Some(addr),
+                Instruction::AddIV(_)
+                | Instruction::LdF(_)
+                | Instruction::LdIV(_)
+                | Instruction::LdVI(_) => None,
+                _ => self.i,
+            },
+        }
+    }
+}
+/// what a subroutine does as seen from a call site
+#[derive(Debug, Clone, Copy)]
+struct Function {
+    writes: u16,
+    returns: bool,
+}
+fn bit(x: usize) -> u16 {
+    1 << (x & 0xF)
+}
+/// V0 to VX
+fn upto(x: usize) -> u16 {
+    ((1u32 << ((x & 0xF) + 1)) - 1) as u16
+}
+fn reads(instruction: Instruction) -> u16 {
+    match instruction {
+        Instruction::SeK(x, _)
+        | Instruction::SneK(x, _)
+        | Instruction::AddK(x, _)
+        | Instruction::Shr(x, _)
+        | Instruction::Shl(x, _)
+        | Instruction::Skp(x)
+        | Instruction::Sknp(x)
+        | Instruction::LdDtV(x)
+        | Instruction::LdStV(x)
+        | Instruction::AddIV(x)
+        | Instruction::LdF(x)
+        | Instruction::LdB(x) => bit(x),
+        Instruction::Se(x, y)
+        | Instruction::Sne(x, y)
+        | Instruction::Or(x, y)
+        | Instruction::And(x, y)
+        | Instruction::Xor(x, y)
+        | Instruction::Add(x, y)
+        | Instruction::Sub(x, y)
+        | Instruction::Subn(x, y)
+        | Instruction::Drw(x, y, _) => bit(x) | bit(y),
+        Instruction::Ld(_, y) => bit(y),
+        Instruction::JpV0(_) => bit(0),
+        Instruction::LdIV(x) => upto(x),
+        _ => 0,
+    }
+}
+fn writes(instruction: Instruction) -> u16 {
+    match instruction {
+        Instruction::LdK(x, _)
+        | Instruction::AddK(x, _)
+        | Instruction::Ld(x, _)
+        | Instruction::Rnd(x, _)
+        | Instruction::LdVDt(x)
+        | Instruction::LdKey(x) => bit(x),
+        Instruction::Or(x, _)
+        | Instruction::And(x, _)
+        | Instruction::Xor(x, _)
+        | Instruction::Add(x, _)
+        | Instruction::Sub(x, _)
+        | Instruction::Shr(x, _)
+        | Instruction::Subn(x, _)
+        | Instruction::Shl(x, _) => bit(x) | bit(0xF),
+        Instruction::Drw(_, _, _) => bit(0xF),
+        Instruction::LdVI(x) => upto(x),
+        _ => 0,
+    }
+}
+/// opcodes only a later interpreter knows, the decoder files them under 0NNN or unknown
+fn extension(instruction: Instruction) -> Option<Profile> {
+    match instruction {
+        Instruction::Sys(0x0C0..=0x0CF) | Instruction::Sys(0x0FB..=0x0FF) => Some(Profile::Schip),
+        Instruction::Drw(_, _, 0) => Some(Profile::Schip),
+        Instruction::Unknown(opcode) => match (opcode >> 12, opcode & 0xF, opcode & 0xFF) {
+            (0x5, 0x2, _) | (0x5, 0x3, _) => Some(Profile::XoChip),
+            (0xF, _, 0x00) | (0xF, _, 0x01) | (0xF, _, 0x02) | (0xF, _, 0x3A) => {
+                Some(Profile::XoChip)
+            }
+            (0xF, _, 0x30) | (0xF, _, 0x75) | (0xF, _, 0x85) => Some(Profile::Schip),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+fn flow(instruction: Instruction) -> Flow {
+    match (extension(instruction), instruction) {
+        (_, Instruction::Sys(0x0FD)) => Flow::Stop,
+        (Some(_), _) => Flow::Next,
+        _ => instruction.flow(),
+    }
+}
+/// everything the linter can tell about a rom without running it
+pub fn lint(rom: &[u8]) -> Report {
+    let cpu = Cpu::load(rom);
+    let mut linter = Linter {
+        cpu: &cpu,
+        cfg: Cfg::build_with(&cpu.memory, PROGRAM_START, flow),
+        end: PROGRAM_START + rom.len().min(cpu.memory.len() - PROGRAM_START),
+        states: BTreeMap::new(),
+        functions: HashMap::new(),
+        findings: Vec::new(),
+        votes: BTreeMap::new(),
+    };
+    linter.walk();
+    linter.check();
+    let (profile, reasons) = linter.recommend();
+    let mut findings = linter.findings;
+    findings.sort_by_key(|finding| (finding.addr, finding.lint));
+    Report {
+        findings,
+        profile,
+        reasons,
+    }
+}
+struct Linter<'a> {
+    cpu: &'a Cpu,
+    /// what is reachable, with extension opcodes stepped over
+    cfg: Cfg,
+    /// first byte past the rom
+    end: usize,
+    /// every reachable instruction and what holds before it runs
+    states: BTreeMap<usize, State>,
+    functions: HashMap<usize, Function>,
+    findings: Vec<Finding>,
+    /// quirk finding address -> the profile that reading of it points to
+    votes: BTreeMap<usize, Profile>,
+}
+impl<'a> Linter<'a> {
+    fn instruction(&self, addr: usize) -> Instruction {
+        self.cpu.instruction_at(addr)
+    }
+    fn in_memory(&self, addr: usize) -> bool {
+        addr + 1 < self.cpu.memory.len()
+    }
+    fn finding(&mut self, addr: usize, lint: Lint, message: String) {
+        self.findings.push(Finding {
+            addr,
+            lint,
+            message,
+        });
+    }
+    /// forward dataflow over the cfg blocks, calls fall through with whatever the callee may write
+    fn walk(&mut self) {
+        let start = State {
+            written: 0,
+            i: Some(0),
+        };
+        let mut entries = BTreeMap::from([(self.cfg.entry, start)]);
+        let mut work = vec![self.cfg.entry];
+        while let Some(leader) = work.pop() {
+            let Some(block) = self.cfg.blocks.get(&leader).cloned() else {
+                continue;
+            };
+            let mut state = entries[&leader];
+            for (addr, instruction) in &block.instructions {
+                self.states.insert(*addr, state);
+                state = state.after(*instruction);
+            }
+            // the return site only gets a state if the callee comes back
+            let returned = match block.instructions.last() {
+                Some((_, Instruction::Call(target))) => {
+                    let function = self.function(*target);
+                    function.returns.then_some(State {
+                        written: state.written | function.writes,
+                        i: None,
+                    })
+                }
+                _ => Some(state),
+            };
+            for (edge, next) in block.successors {
+                let state = match edge {
+                    Edge::Computed => continue,
+                    Edge::Next => match returned {
+                        Some(returned) => returned,
+                        None => continue,
+                    },
+                    _ => state,
+                };
+                let merged = match entries.get(&next) {
+                    Some(old) => old.meet(state),
+                    None => state,
+                };
+                if entries.get(&next) != Some(&merged) {
+                    entries.insert(next, merged);
+                    work.push(next);
+                }
+            }
+        }
+    }
+    /// the blocks of a subroutine up to its 00EE, stepping over nested calls
+    fn function(&mut self, start: usize) -> Function {
+        if let Some(function) = self.functions.get(&start) {
+            return *function;
+        }
+        // recursion assumes the best, the outer call finishes the answer
+        self.functions.insert(
+            start,
+            Function {
+                writes: 0,
+                returns: true,
+            },
+        );
+        let mut function = Function {
+            writes: 0,
+            returns: false,
+        };
+        let mut seen = BTreeSet::new();
+        let mut work = vec![start];
+        while let Some(leader) = work.pop() {
+            if !seen.insert(leader) {
+                continue;
+            }
+            let Some(block) = self.cfg.blocks.get(&leader).cloned() else {
+                continue;
+            };
+            for (_, instruction) in &block.instructions {
+                function.writes |= writes(*instruction);
+            }
+            if let Some((_, instruction)) = block.instructions.last() {
+                function.returns |= flow(*instruction) == Flow::Return;
+            }
+            for (edge, next) in block.successors {
+                match edge {
+                    Edge::Call => function.writes |= self.function(next).writes,
+                    Edge::Computed => (),
+                    _ => work.push(next),
+                }
+            }
+        }
+        self.functions.insert(start, function);
+        function
+    }
+    /// true if some reachable instruction covers `addr`
+    fn is_code(&self, addr: usize) -> bool {
+        self.states.contains_key(&addr) || self.states.contains_key(&addr.wrapping_sub(1))
+    }
+    fn check(&mut self) {
+        let states: Vec<(usize, State)> = self.states.iter().map(|(a, s)| (*a, *s)).collect();
+        let mut called = BTreeSet::new();
+        for (addr, state) in states {
+            let instruction = self.instruction(addr);
+            match instruction {
+                Instruction::Jp(target) | Instruction::Call(target) | Instruction::JpV0(target) => {
+                    self.check_target(addr, instruction, target)
+                }
+                Instruction::Sys(nnn) if extension(instruction).is_none() => self.finding(
+                    addr,
+                    Lint::MachineCall,
+                    format!(
+                        "{} calls 1802 code at {:03X}, nothing but a cosmac vip runs it",
+                        instruction, nnn
+                    ),
+                ),
+                _ => (),
+            }
+            if let Instruction::Call(target) = instruction {
+                if called.insert(target) && !self.function(target).returns {
+                    self.finding(
+                        addr,
+                        Lint::MissingReturn,
+                        format!("{} never reaches a 00EE, the stack only grows", instruction),
+                    );
+                }
+            }
+            let unset = reads(instruction) & !state.written;
+            if unset != 0 {
+                let registers: Vec<String> = (0..16)
+                    .filter(|r| unset & bit(*r) != 0)
+                    .map(|r| format!("V{:X}", r))
+                    .collect();
+                self.finding(
+                    addr,
+                    Lint::Uninitialized,
+                    format!(
+                        "{} reads {} before anything writes it",
+                        instruction,
+                        registers.join(", ")
+                    ),
+                );
+            }
+            self.check_store(addr, instruction, state);
+            self.check_quirks(addr, instruction, state);
+        }
+        self.check_unreachable();
+    }
+    fn check_target(&mut self, addr: usize, instruction: Instruction, target: usize) {
+        if target % 2 == 1 {
+            let message = if self.states.contains_key(&(target - 1)) {
+                format!(
+                    "{} lands inside the instruction at {:03X}",
+                    instruction,
+                    target - 1
+                )
+            } else {
+                format!("{} goes to an odd address", instruction)
+            };
+            self.finding(addr, Lint::OddJump, message);
+        }
+    }
+    /// FX55 and FX33 write through I, with I known the range can be checked against code
+    fn check_store(&mut self, addr: usize, instruction: Instruction, state: State) {
+        let len = match instruction {
+            Instruction::LdIV(x) => x + 1,
+            Instruction::LdB(_) => 3,
+            _ => return,
+        };
+        if let Some(i) = state.i {
+            if let Some(code) = (i..i + len).find(|byte| self.is_code(*byte)) {
+                self.finding(
+                    addr,
+                    Lint::SelfModifying,
+                    format!(
+                        "{} writes {:03X}-{:03X} over code at {:03X}",
+                        instruction,
+                        i,
+                        i + len - 1,
+                        code
+                    ),
+                );
+            }
+        }
+    }
+    /// what the code around a quirky opcode writes says which interpreter its author ran
+    fn check_quirks(&mut self, addr: usize, instruction: Instruction, state: State) {
+        let written = |x: usize| state.written & bit(x) != 0;
+        let (message, vote) = match instruction {
+            // nobody loads VY just to shift VX in place
+            Instruction::Shr(x, y) | Instruction::Shl(x, y) if x != y => (
+                format!(
+                    "{} shifts VY into VX on the vip, VX in place on schip",
+                    instruction.pattern()
+                ),
+                Some(if written(y) {
+                    Profile::Vip
+                } else {
+                    Profile::Schip
+                }),
+            ),
+            Instruction::JpV0(nnn) if nnn >> 8 != 0 => (
+                "BNNN adds V0 on the vip, VX on schip".to_string(),
+                match (written(0), written(nnn >> 8)) {
+                    (true, false) => Some(Profile::Vip),
+                    (false, true) => Some(Profile::Schip),
+                    _ => None,
+                },
+            ),
+            Instruction::LdIV(_) | Instruction::LdVI(_) if self.uses_moved_i(addr) => (
+                format!(
+                    "{} leaves I past VX on the vip and untouched on schip, and I is used next",
+                    instruction.pattern()
+                ),
+                Some(Profile::Vip),
+            ),
+            _ => return,
+        };
+        self.finding(addr, Lint::Quirk, message);
+        if let Some(profile) = vote {
+            self.votes.insert(addr, profile);
+        }
+    }
+    /// straight line code after FX55/FX65 that reads I before setting it
+    fn uses_moved_i(&self, mut addr: usize) -> bool {
+        loop {
+            addr += 2;
+            if !self.in_memory(addr) {
+                return false;
+            }
+            match self.instruction(addr) {
+                Instruction::LdI(_) | Instruction::LdF(_) => return false,
+                Instruction::Drw(_, _, _)
+                | Instruction::AddIV(_)
+                | Instruction::LdB(_)
+                | Instruction::LdIV(_)
+                | Instruction::LdVI(_) => return true,
+                instruction if instruction.flow() != Flow::Next => return false,
+                _ => (),
+            }
+        }
+    }
+    /// nonzero runs of the rom that are not code and not right behind an ANNN
+    fn check_unreachable(&mut self) {
+        let mut data = BTreeSet::new();
+        for addr in self.states.keys() {
+            if let Instruction::LdI(target) = self.instruction(*addr) {
+                // one sprite or one FX65 worth
+                data.extend((target..target + 16).take_while(|byte| !self.is_code(*byte)));
+            }
+        }
+        let mut run: Option<usize> = None;
+        for addr in PROGRAM_START..=self.end {
+            let dead = addr < self.end && !self.is_code(addr) && !data.contains(&addr);
+            match (run, dead) {
+                (None, true) => run = Some(addr),
+                (Some(start), false) => {
+                    run = None;
+                    if self.cpu.memory[start..addr].iter().any(|byte| *byte != 0) {
+                        self.finding(
+                            start,
+                            Lint::Unreachable,
+                            format!("{:03X}-{:03X} is never run or pointed at", start, addr - 1),
+                        );
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+    /// extension opcodes settle it, otherwise the quirk findings vote and a tie goes to the vip
+    fn recommend(&self) -> (Profile, Vec<String>) {
+        let mut reasons = Vec::new();
+        let mut profile = None;
+        for addr in self.states.keys() {
+            let instruction = self.instruction(*addr);
+            if let Some(extension) = extension(instruction) {
+                reasons.push(format!(
+                    "{:03X}: {:04X} is a {} opcode",
+                    addr,
+                    (self.cpu.memory[*addr] as u8 as u16) << 8
+                        | self.cpu.memory[*addr + 1] as u8 as u16,
+                    extension
+                ));
+                if profile != Some(Profile::XoChip) {
+                    profile = Some(extension);
+                }
+            }
+        }
+        if let Some(profile) = profile {
+            return (profile, reasons);
+        }
+        let mut tally = [0, 0];
+        for finding in self
+            .findings
+            .iter()
+            .filter(|finding| finding.lint == Lint::Quirk)
+        {
+            let vote = match self.votes.get(&finding.addr) {
+                Some(profile) => {
+                    tally[(*profile == Profile::Schip) as usize] += 1;
+                    format!("a {} vote", profile)
+                }
+                None => "no vote".to_string(),
+            };
+            reasons.push(format!(
+                "{:03X}: {}, {}",
+                finding.addr, finding.message, vote
+            ));
+        }
+        if reasons.is_empty() {
+            reasons.push("nothing quirk sensitive, any profile runs it the same".to_string());
+        }
+        // the vip is the original, roms that do not care were mostly written for it
+        let [vip, schip] = tally;
+        if schip > vip {
+            (Profile::Schip, reasons)
+        } else {
+            (Profile::Vip, reasons)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lints(rom: &[u8]) -> Vec<(usize, Lint)> {
+        lint(rom)
+            .findings
+            .iter()
+            .map(|finding| (finding.addr, finding.lint))
+            .collect()
+    }
+
+    #[test]
+    fn clean_loop() {
+        // 200: LD V0, 01  202: ADD V0, 01  204: JP 202
+        assert_eq!(lints(&[0x60, 0x01, 0x70, 0x01, 0x12, 0x02]), vec![]);
+    }
+
+    #[test]
+    fn finds_each_lint() {
+        let rom = [
+            0x22, 0x0A, // 200: CALL 20A
+            0x81, 0x26, // 202: never reached, the call does not come back
+            0x03, 0x00, //
+            0xAB, 0xCD, //
+            0x12, 0x0D, //
+            0xA2, 0x00, // 20A: LD   I, 200
+            0xF0, 0x55, // 20C: LD   [I], V0 over the call
+            0x12, 0x0E, // 20E: JP 20E, never returns
+        ];
+        assert_eq!(
+            lints(&rom),
+            vec![
+                (0x200, Lint::MissingReturn),
+                (0x202, Lint::Unreachable),
+                (0x20C, Lint::Uninitialized),
+                (0x20C, Lint::SelfModifying),
+            ]
+        );
+        let rom = [
+            0x81, 0x26, // 200: SHR  V1, V2 reads V1, quirk
+            0x60, 0x00, // 202: LD   V0, 00
+            0x30, 0x00, // 204: SE   V0, 00
+            0x12, 0x09, // 206: JP   209, inside the SYS
+            0x03, 0x00, // 208: SYS  300
+        ];
+        assert_eq!(
+            lints(&rom),
+            vec![
+                (0x200, Lint::Uninitialized),
+                (0x200, Lint::Quirk),
+                (0x206, Lint::OddJump),
+                (0x208, Lint::MachineCall),
+            ]
+        );
+    }
+
+    #[test]
+    fn steps_over_extensions() {
+        // 200: HIGH  202: LD V0, 01  204: JP 204, all reachable past the schip opcode
+        assert_eq!(lints(&[0x00, 0xFF, 0x60, 0x01, 0x12, 0x04]), vec![]);
+    }
+
+    #[test]
+    fn recommends_profile() {
+        let profile = |rom: &[u8]| lint(rom).profile;
+        // 200: HIGH, a superchip opcode settles it
+        assert_eq!(profile(&[0x00, 0xFF, 0x12, 0x02]), Profile::Schip);
+        // 200: LD V1, 05  202: SHR V0, V1, VY was loaded to be shifted
+        assert_eq!(profile(&[0x61, 0x05, 0x80, 0x16]), Profile::Vip);
+        // 200: LD V0, 05  202: SHR V0, V1, VY is never set so VX shifts in place
+        assert_eq!(profile(&[0x60, 0x05, 0x80, 0x16]), Profile::Schip);
+        // 200: LD V0, 02  202: JP V0, 300
+        assert_eq!(profile(&[0x60, 0x02, 0xB3, 0x00]), Profile::Vip);
+        // 200: LD V3, 02  202: JP V3, 300
+        assert_eq!(profile(&[0x63, 0x02, 0xB3, 0x00]), Profile::Schip);
+        // 200: LD I, 300  202: LD [I], V1  204: LD [I], V1, the second store relies on I moving
+        assert_eq!(profile(&[0xA3, 0x00, 0xF1, 0x55, 0xF1, 0x55]), Profile::Vip);
+        // a vip and a schip vote tie, and nothing quirky at all
+        assert_eq!(profile(&[0x61, 0x05, 0x80, 0x16, 0x82, 0x36]), Profile::Vip);
+        assert_eq!(profile(&[0x60, 0x01, 0x12, 0x02]), Profile::Vip);
+    }
+}
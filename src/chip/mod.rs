@@ -0,0 +1,806 @@
+// headless chip, no bevy in here
+// frontends (app, tui) own a Cpu, call run, then draw cpu.screen
+pub mod cfg;
+pub mod clock;
+pub mod dynarec;
+pub mod labels;
+pub mod lint;
+pub mod opcode;
+pub mod payload;
+pub mod profile;
+pub mod quirks;
+pub mod screen;
+pub mod sprite;
+
+use self::{
+    clock::Tick,
+    opcode::Instruction,
+    quirks::{Profile, Quirks},
+    screen::{Screen, SCREEN_X, SCREEN_Y},
+};
+
+macro_rules! tuple_as {
+    ($t: expr, $ty: ident) => {{
+        let (a, b) = $t;
+        let a = a as $ty;
+        let b = b as $ty;
+        (a, b)
+    }};
+    ($t: expr, ($ty: ident)) => {{
+        let (a, b) = $t;
+        let a = a as $ty;
+        let b = b as $ty;
+        (a, b)
+    }};
+    ($t: expr, ($($ty: ident),*)) => {{
+        let ($($ty,)*) = $t;
+        ($($ty as $ty,)*)
+    }};
+}
+const STATUS_REGISTER: usize = 0xF;
+/// where roms are loaded, everything below is reserved for the interpreter
+pub const PROGRAM_START: usize = 0x200;
+/// where FX29 finds the hex digits, clear of the demo program at 0x000 and 0x100
+pub const FONT_START: usize = 0x50;
+/// 0 to F, 4x5 pixels each in the high nibble of five bytes
+pub const FONT: [u8; 80] = [
+    0xF0, 0x90, 0x90, 0x90, 0xF0, // 0
+    0x20, 0x60, 0x20, 0x20, 0x70, // 1
+    0xF0, 0x10, 0xF0, 0x80, 0xF0, // 2
+    0xF0, 0x10, 0xF0, 0x10, 0xF0, // 3
+    0x90, 0x90, 0xF0, 0x10, 0x10, // 4
+    0xF0, 0x80, 0xF0, 0x10, 0xF0, // 5
+    0xF0, 0x80, 0xF0, 0x90, 0xF0, // 6
+    0xF0, 0x10, 0x20, 0x40, 0x40, // 7
+    0xF0, 0x90, 0xF0, 0x90, 0xF0, // 8
+    0xF0, 0x90, 0xF0, 0x10, 0xF0, // 9
+    0xF0, 0x90, 0xF0, 0x90, 0x90, // A
+    0xE0, 0x90, 0xE0, 0x90, 0xE0, // B
+    0xF0, 0x80, 0x80, 0x80, 0xF0, // C
+    0xE0, 0x90, 0x90, 0x90, 0xE0, // D
+    0xF0, 0x80, 0xF0, 0x80, 0xF0, // E
+    0xF0, 0x80, 0xF0, 0x80, 0x80, // F
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cpu {
+    pub registers: [usize; usize::BITS as usize],
+    pub stack: [usize; usize::BITS as usize * 4],
+    pub memory: [usize; 4096],
+    pub counter: usize,
+    pub pointer: usize,
+    pub i: usize,
+    pub delay: usize,
+    pub sound: usize,
+    pub keys: [bool; 16],
+    pub screen: Screen,
+    /// state of the CXKK generator, copy a cpu to replay its random numbers too
+    pub seed: u64,
+    /// answers to the opcodes interpreters disagree on, superchip unless a frontend picks
+    pub quirks: Quirks,
+}
+impl Default for Cpu {
+    fn default() -> Self {
+        Cpu {
+            registers: default_registers(),
+            stack: [0; usize::BITS as usize * 4],
+            memory: default_memory(),
+            counter: 0,
+            pointer: 0,
+            i: 0,
+            delay: 0,
+            sound: 0,
+            keys: [false; 16],
+            screen: Screen::default(),
+            seed: seed(),
+            quirks: Profile::Schip.quirks(),
+        }
+    }
+}
+impl Cpu {
+    pub fn new() -> Cpu {
+        Cpu::default()
+    }
+    /// blank machine with the font, `rom` copied to PROGRAM_START and the counter pointing at it
+    pub fn load(rom: &[u8]) -> Cpu {
+        let mut cpu = Cpu {
+            registers: [0; usize::BITS as usize],
+            memory: font_memory(),
+            counter: PROGRAM_START,
+            ..Cpu::default()
+        };
+        for (i, byte) in rom
+            .iter()
+            .take(cpu.memory.len() - PROGRAM_START)
+            .enumerate()
+        {
+            cpu.memory[PROGRAM_START + i] = *byte as usize;
+        }
+        cpu
+    }
+    /// where the program starts, roms live at PROGRAM_START and the demo program at 0
+    pub fn entry(&self) -> usize {
+        if self.memory[PROGRAM_START..].iter().any(|byte| *byte != 0) {
+            PROGRAM_START
+        } else {
+            0
+        }
+    }
+    /// one frame worth of work handed out by a Clock
+    pub fn advance(&mut self, tick: Tick) {
+        for _ in 0..tick.cycles {
+            self.run();
+        }
+        for _ in 0..tick.timers {
+            self.tick_timers();
+        }
+    }
+    /// 60hz countdown of delay and sound
+    pub fn tick_timers(&mut self) {
+        self.delay = self.delay.saturating_sub(1);
+        self.sound = self.sound.saturating_sub(1);
+    }
+    /// the counter wraps at the end of memory like every other address
+    pub fn read_opcode(&self) -> (usize, usize) {
+        (
+            self.memory[self.counter & 0xFFF],
+            self.memory[(self.counter + 1) & 0xFFF],
+        )
+    }
+    /// decodes whatever sits at `addr`, used by disassembly views
+    pub fn instruction_at(&self, addr: usize) -> Instruction {
+        let hi = self.memory.get(addr).copied().unwrap_or(0);
+        let lo = self.memory.get(addr + 1).copied().unwrap_or(0);
+        Instruction::decode((hi as u8 as u16) << 8 | lo as u8 as u16)
+    }
+    /// DXYN | Sprites stored in memory at location in index register (I), 8bits wide. VX, VY wrap onto the screen, the sprite is then clipped at the edges or wraps around depending on Quirks::draw_clips. If when drawn, clears a pixel, register VF is set to 1 otherwise it is zero. All drawing is XOR drawing (i.e. it toggles the screen pixels). Sprites are drawn starting at position VX, VY. N is the number of 8bit rows that need to be drawn. If N is greater than 1, second line continues at position VX, VY+1, and so on.
+    fn drw(&mut self, x: usize, y: usize, n: usize) {
+        let (left, top) = (self.registers[x] % SCREEN_X, self.registers[y] % SCREEN_Y);
+        let mut vf = false;
+        for i in 0..n {
+            let sprite_byte = self.memory[(self.i + i) & 0xFFF];
+            for j in 0..8 {
+                if (sprite_byte >> (7 - j)) & 1 == 0 {
+                    continue;
+                }
+                let (x, y) = (left + j, top + i);
+                if self.quirks.draw_clips && (x >= SCREEN_X || y >= SCREEN_Y) {
+                    continue;
+                }
+                let pixel = x % SCREEN_X + (y % SCREEN_Y) * SCREEN_X;
+                if self.screen.0[pixel] {
+                    vf = true
+                }
+                self.screen.flp(pixel);
+            }
+        }
+        self.registers[STATUS_REGISTER] = vf as usize;
+    }
+    pub fn run(&mut self) {
+        if self.counter == 0 {
+            // println!(
+            //     "\n\n\n CYCLE\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tRUN",
+            //     self.pointer,
+            //     self.i,
+            //     self.counter,
+            //     &self.registers[0..16],
+            //     &self.stack[0..16]
+            // );
+        }
+        let opcode_bytes = self.read_opcode();
+        self.counter += 2;
+
+        let opcode = (opcode_bytes.0 as u8 as u16) << 8 | opcode_bytes.1 as u8 as u16;
+        // println!(
+        //     "\n    OP\t{:04X?}\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t{:04X?}\t{:04X?}\t{:04X?}\n",
+        //     opcode, opcode, opcode, opcode
+        // );
+        self.execute(Instruction::decode(opcode));
+        // println!(
+        //     "  LOOP\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+    }
+    /// runs one already decoded instruction, the counter has already moved past it
+    pub fn execute(&mut self, instruction: Instruction) {
+        match instruction {
+            Instruction::Exit => self.screen.clr(),
+            Instruction::Cls => self.screen.clr(),
+            Instruction::Ret => self.ret(),
+            Instruction::Sys(_) => self.stop(), // Not necessary for most ROMs.
+            Instruction::Jp(addr) => self.jp(addr),
+            Instruction::Call(addr) => self.call(addr),
+            Instruction::SeK(x, kk) => self.skp(self.registers[x] == kk),
+            Instruction::SneK(x, kk) => self.skp(self.registers[x] != kk),
+            Instruction::Se(x, y) => self.skp(self.registers[x] == self.registers[y]),
+            Instruction::LdK(x, kk) => self.ldk(x, kk),
+            Instruction::AddK(x, kk) => self.addk(x, kk),
+            Instruction::Ld(x, y) => self.ld(x, y),
+            Instruction::Or(x, y) => self.or(x, y),
+            Instruction::And(x, y) => self.and(x, y),
+            Instruction::Xor(x, y) => self.xor(x, y),
+            Instruction::Add(x, y) => self.add(x, y), // VF is set to 1 when there's a carry, and to 0 when there isn't.
+            Instruction::Sub(x, y) => self.sub(x, y), // VF is set to 0 when there's a borrow, and 1 when there isn't.
+            Instruction::Shr(x, y) => self.shr(x, y), // VF is set to the value of the least significant bit of VX before the shift.
+            Instruction::Subn(x, y) => self.subn(x, y), // VF is set to 0 when there's a borrow, and 1 when there isn't.
+            Instruction::Shl(x, y) => self.shl(x, y), // VF is set to the value of the most significant bit of VX before the shift
+            Instruction::Sne(x, y) => self.skp(self.registers[x] != self.registers[y]),
+            Instruction::LdI(addr) => self.ldik(addr),
+            Instruction::JpV0(addr) => self.jp0(addr),
+            Instruction::Rnd(x, kk) => self.rnd(x, kk),
+            Instruction::Drw(x, y, n) => self.drw(x, y, n),
+            Instruction::Skp(x) => self.skp(self.keys[self.registers[x] & 0xF]),
+            Instruction::Sknp(x) => self.skp(!self.keys[self.registers[x] & 0xF]),
+            Instruction::LdVDt(x) => self.ldd(x),
+            Instruction::LdKey(x) => self.ldkb(x),
+            Instruction::LdDtV(x) => self.lddv(x),
+            Instruction::LdStV(x) => self.ldsv(x),
+            Instruction::AddIV(x) => self.addiv(x),
+            Instruction::LdF(x) => self.ldf(x),
+            Instruction::LdB(x) => self.ldb(x),
+            Instruction::LdIV(x) => self.ldir(x),
+            Instruction::LdVI(x) => self.ldri(x),
+            Instruction::Unknown(_) => self.stop(), // _ | Stop.
+        }
+    }
+    /// stays on the opcode it cannot run, like a jump to itself, so frontends keep drawing
+    fn stop(&mut self) {
+        self.counter -= 2;
+    }
+    fn skp(&mut self, bool: bool) {
+        if bool {
+            self.counter += 2;
+        }
+    }
+    fn call(&mut self, addr: usize) {
+        // println!(
+        //     "  CALL\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tCALL\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        if self.pointer >= self.stack.len() {
+            // stack overflow, stop on the call
+            self.stop();
+            return;
+        }
+        self.stack[self.pointer] = self.counter;
+        self.pointer += 1;
+        self.counter = addr;
+    }
+    fn ret(&mut self) {
+        // println!(
+        //     "   RET\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tRET\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        if self.pointer == 0 {
+            // panic!("Stack underflow")
+            self.counter = self.stack[self.pointer];
+            return;
+        }
+        self.pointer -= 1;
+        self.counter = self.stack[self.pointer];
+    }
+    fn jp(&mut self, addr: usize) {
+        // println!(
+        //     "  JUMP\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tJUMP\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        self.counter = addr;
+    }
+    fn add(&mut self, x: usize, y: usize) {
+        // println!(
+        //     "   ADD\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tADD\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        (self.registers[x], self.registers[STATUS_REGISTER]) = tuple_as!(
+            (self.registers[x] as u8).overflowing_add(self.registers[y] as u8),
+            usize
+        );
+    }
+    fn addk(&mut self, x: usize, kk: usize) {
+        // println!(
+        //     "   ADD\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tADD\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        (self.registers[x], self.registers[STATUS_REGISTER]) =
+            tuple_as!((self.registers[x] as u8).overflowing_add(kk as u8), usize);
+    }
+    fn sub(&mut self, x: usize, y: usize) {
+        // println!(
+        //     "   SUB\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tSUB\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        (self.registers[x], self.registers[STATUS_REGISTER]) = tuple_as!(
+            (self.registers[x] as u8).overflowing_sub(self.registers[y] as u8),
+            usize
+        );
+    }
+    fn subn(&mut self, x: usize, y: usize) {
+        // println!(
+        //     "   SUB\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tSUB\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        (self.registers[x], self.registers[STATUS_REGISTER]) = tuple_as!(
+            (self.registers[y] as u8).overflowing_sub(self.registers[x] as u8),
+            usize
+        );
+    }
+    fn shl(&mut self, x: usize, y: usize) {
+        // println!(
+        //     "   SHL\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tSHIFT\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        let value = self.shifted(x, y);
+        (self.registers[x], self.registers[STATUS_REGISTER]) =
+            tuple_as!((value << 1, value >> 7), usize);
+    }
+    fn shr(&mut self, x: usize, y: usize) {
+        // println!(
+        //     "   SHR\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tSHIFT\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        let value = self.shifted(x, y);
+        (self.registers[x], self.registers[STATUS_REGISTER]) =
+            tuple_as!((value >> 1, value & 1), usize);
+    }
+    /// 8XY6/8XYE shift VY on the vip and VX in place on the superchip
+    fn shifted(&self, x: usize, y: usize) -> u8 {
+        if self.quirks.shift_uses_vy {
+            self.registers[y] as u8
+        } else {
+            self.registers[x] as u8
+        }
+    }
+    fn ldk(&mut self, x: usize, kk: usize) {
+        // println!(
+        //     "   SET\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tSET\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        self.registers[x] = kk;
+    }
+    fn ldik(&mut self, addr: usize) {
+        // println!(
+        //     "   SETI\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tSET\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        self.i = addr;
+    }
+    fn ld(&mut self, x: usize, y: usize) {
+        // println!(
+        //     "   LDR\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tSET\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        self.registers[x] = self.registers[y];
+    }
+    fn addiv(&mut self, x: usize) {
+        // println!(
+        //     "   LDI\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tSET\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        self.i = (self.i + self.registers[x]) & 0xFFF;
+    }
+    /// FX0A, waits on this opcode until a key is down
+    fn ldkb(&mut self, x: usize) {
+        match self.keys.iter().position(|key| *key) {
+            Some(key) => self.registers[x] = key,
+            None => self.stop(),
+        }
+    }
+    fn ldf(&mut self, x: usize) {
+        self.i = FONT_START + (self.registers[x] & 0xF) * 5;
+    }
+    /// FX33, hundreds, tens and ones of VX at I, I+1 and I+2
+    fn ldb(&mut self, x: usize) {
+        let value = self.registers[x] as u8 as usize;
+        for (offset, digit) in [value / 100, value / 10 % 10, value % 10]
+            .into_iter()
+            .enumerate()
+        {
+            self.memory[(self.i + offset) & 0xFFF] = digit;
+        }
+    }
+    /// FX55, V0 to VX into memory at I
+    fn ldir(&mut self, x: usize) {
+        for register in 0..=x {
+            self.memory[(self.i + register) & 0xFFF] = self.registers[register];
+        }
+        self.load_store_moves_i(x);
+    }
+    /// FX65, V0 to VX from memory at I
+    fn ldri(&mut self, x: usize) {
+        for register in 0..=x {
+            self.registers[register] = self.memory[(self.i + register) & 0xFFF];
+        }
+        self.load_store_moves_i(x);
+    }
+    /// the vip walks I past VX while copying
+    fn load_store_moves_i(&mut self, x: usize) {
+        if self.quirks.load_store_moves_i {
+            self.i += x + 1;
+        }
+    }
+    fn or(&mut self, x: usize, y: usize) {
+        // println!(
+        //     "   OR\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tOR\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        self.registers[x] = self.registers[x] | self.registers[y];
+        self.logic_resets_vf();
+    }
+    fn xor(&mut self, x: usize, y: usize) {
+        // println!(
+        //     "   XOR\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tXOR\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        self.registers[x] = self.registers[x] ^ self.registers[y];
+        self.logic_resets_vf();
+    }
+    fn and(&mut self, x: usize, y: usize) {
+        // println!(
+        //     "   AND\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tAND\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        self.registers[x] = self.registers[x] & self.registers[y];
+        self.logic_resets_vf();
+    }
+    /// 8XY1/8XY2/8XY3 clobber VF on the vip
+    fn logic_resets_vf(&mut self) {
+        if self.quirks.logic_resets_vf {
+            self.registers[STATUS_REGISTER] = 0;
+        }
+    }
+    fn ldd(&mut self, x: usize) {
+        // println!(
+        //     "   LDDT\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tLDDT\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        self.registers[x] = self.delay;
+    }
+    fn lddv(&mut self, x: usize) {
+        // println!(
+        //     "   LDDTV\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tLDDT\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        self.delay = self.registers[x];
+    }
+    fn ldsv(&mut self, x: usize) {
+        // println!(
+        //     "   LDST\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tLDST\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        self.sound = self.registers[x];
+    }
+    /// BNNN, the superchip reads it as BXNN and adds VX instead of V0
+    fn jp0(&mut self, addr: usize) {
+        // println!(
+        //     "   JPV0\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tJPV0\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        let offset = if self.quirks.jump_uses_vx {
+            self.registers[addr >> 8]
+        } else {
+            self.registers[0]
+        };
+        self.counter = (addr + offset) & 0xFFF;
+    }
+    fn rnd(&mut self, x: usize, kk: usize) {
+        // println!(
+        //     "   RND\tp:{:?}\ti:{:?}\tc:{:04X?}\tr:{:?}\ts:{:X?}\tRND\n",
+        //     self.pointer,
+        //     self.i,
+        //     self.counter,
+        //     &self.registers[0..16],
+        //     &self.stack[0..16]
+        // );
+        self.registers[x] = self.random() as u8 as usize & kk;
+    }
+    /// xorshift64*, plenty for games and keeps the core free of crates
+    fn random(&mut self) -> usize {
+        self.seed ^= self.seed >> 12;
+        self.seed ^= self.seed << 25;
+        self.seed ^= self.seed >> 27;
+        self.seed.wrapping_mul(0x2545_F491_4F6C_DD1D) as usize
+    }
+}
+/// std hashes with per process random keys, on targets without them the shell should set `seed`
+fn seed() -> u64 {
+    use std::hash::{BuildHasher, Hasher};
+    std::collections::hash_map::RandomState::new()
+        .build_hasher()
+        .finish()
+        | 1
+}
+/// empty memory apart from the font at FONT_START
+pub fn font_memory() -> [usize; 4096] {
+    let mut memory = [0; 4096];
+    for (i, byte) in FONT.iter().enumerate() {
+        memory[FONT_START + i] = *byte as usize;
+    }
+    memory
+}
+pub fn default_memory() -> [usize; 4096] {
+    let mut memory = font_memory();
+    memory[0x0] = 0x21; // call fn add twice // 2NNN | Calls subroutine at NNN.
+    memory[0x2] = 0x21; // call fn sub twice // 2NNN | Calls subroutine at NNN.
+    memory[0x3] = 0x06;
+    memory[0x4] = 0x21; // call fn add twice then sub twice // 2NNN | Calls subroutine at NNN.
+    memory[0x5] = 0x0C;
+    // leave two bytes for the program return // 0000 | Returns the program.
+
+    // fn add twice
+    memory[0x100] = 0x80; // 8XY4 | Adds VY to VX. VF is set to 1 when there's a carry,
+    memory[0x101] = 0x14;
+    memory[0x102] = 0x80; // 8XY4 | Adds VY to VX. VF is set to 1 when there's a carry,
+    memory[0x103] = 0x14;
+    memory[0x105] = 0xEE; // leave two bytes for the subroutine return // 00EE | Returns from a subroutine.
+
+    // fn sub twice
+    memory[0x106] = 0x80; // 8XY5 | VY is subtracted from VX. VF is set to 0 whedin there's a borrow, and 1 when there isn't.
+    memory[0x107] = 0x15;
+    memory[0x108] = 0x80; // 8XY5 | VY is subtracted from VX. VF is set to 0 whedin there's a borrow, and 1 when there isn't.
+    memory[0x109] = 0x15;
+    memory[0x10B] = 0xEE; // leave two bytes for the subroutine return // 00EE | Returns from a subroutine.
+
+    // fn add twice then call sub twice
+    memory[0x10C] = 0x21; // call fn add twice // 2NNN | Calls subroutine at NNN.
+    memory[0x10E] = 0x21; // call fn sub twice // 2NNN | Calls subroutine at NNN.
+    memory[0x10F] = 0x06;
+    // mem[0x10F] = 0x0C;
+    memory[0x111] = 0xEE; // leave two bytes for the subroutine return // 00EE | Returns from a subroutine.
+    memory
+}
+pub fn default_registers() -> [usize; usize::BITS as usize] {
+    let mut registers = [0; usize::BITS as usize];
+    registers[0] = 42;
+    registers[1] = 5;
+    registers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn copies_replay_random_numbers() {
+        let rom = [
+            0xC0, 0x4F, // 200: RND  V0, 4F
+            0x12, 0x00, // 202: JP   200
+        ];
+        let mut cpu = Cpu::load(&rom);
+        let mut copy = cpu;
+        let mut draws = std::collections::BTreeSet::new();
+        for _ in 0..64 {
+            cpu.run();
+            copy.run();
+            assert_eq!(cpu, copy);
+            draws.insert(cpu.registers[0]);
+        }
+        assert!(draws.len() > 8);
+        // masked with the byte, so nothing outside its bits
+        assert!(draws.iter().all(|draw| *draw <= 0x4F && *draw & !0x4F == 0));
+    }
+
+    #[test]
+    fn follows_quirks() {
+        let rom = [
+            0x60, 0x05, // 200: LD   V0, 05
+            0x61, 0x81, // 202: LD   V1, 81
+            0x80, 0x16, // 204: SHR  V0, V1
+            0xA3, 0x00, // 206: LD   I, 300
+            0xF1, 0x55, // 208: LD   [I], V1
+            0x82, 0x01, // 20A: OR   V2, V0
+            0xB1, 0x10, // 20C: JP   V0, 110
+        ];
+        let mut vip = Cpu::load(&rom);
+        vip.quirks = Profile::Vip.quirks();
+        let mut schip = Cpu::load(&rom);
+        schip.quirks = Profile::Schip.quirks();
+        for _ in 0..rom.len() / 2 {
+            vip.run();
+            schip.run();
+        }
+        // VY shifted into VX, I past V1, VF cleared by the OR, NNN + V0
+        assert_eq!(vip.registers[0], 0x40);
+        assert_eq!(vip.i, 0x302);
+        assert_eq!(vip.registers[0xF], 0);
+        assert_eq!(vip.counter, 0x150);
+        // VX shifted in place, I untouched, VF from the shift, NNN + V1
+        assert_eq!(schip.registers[0], 0x02);
+        assert_eq!(schip.i, 0x300);
+        assert_eq!(schip.registers[0xF], 1);
+        assert_eq!(schip.counter, 0x191);
+        assert_eq!(schip.memory[0x301], 0x81);
+    }
+
+    #[test]
+    fn draws_at_vx_vy() {
+        let rom = [
+            0x60, 0x10, // 200: LD   V0, 10
+            0xA2, 0x0A, // 202: LD   I, 20A
+            0xD0, 0x15, // 204: DRW  V0, V1, 5
+            0xD0, 0x15, // 206: DRW  V0, V1, 5
+            0x12, 0x08, // 208: JP   208
+            0xF0, 0x90, 0x90, 0x90, 0xF0, // 20A: a zero
+        ];
+        let mut cpu = Cpu::load(&rom);
+        for _ in 0..3 {
+            cpu.run();
+        }
+        assert!(cpu.screen.get(0x10, 0) && cpu.screen.get(0x13, 4));
+        assert!(!cpu.screen.get(0x11, 1) && !cpu.screen.get(0x14, 0));
+        assert_eq!(cpu.registers[STATUS_REGISTER], 0);
+        // xor, drawing it again erases it and reports the collision
+        cpu.run();
+        assert!(!cpu.screen.get(0x10, 0));
+        assert_eq!(cpu.registers[STATUS_REGISTER], 1);
+    }
+
+    #[test]
+    fn clips_or_wraps_at_the_edge() {
+        let rom = [
+            0x60, 0x7E, // 200: LD   V0, 7E, 3E once wrapped
+            0x61, 0x1F, // 202: LD   V1, 1F
+            0xA2, 0x08, // 204: LD   I, 208
+            0xD0, 0x12, // 206: DRW  V0, V1, 2
+            0xFF, 0xFF, // 208: two full rows
+        ];
+        let mut clips = Cpu::load(&rom);
+        clips.quirks = Profile::Vip.quirks();
+        let mut wraps = Cpu::load(&rom);
+        wraps.quirks = Profile::XoChip.quirks();
+        for _ in 0..4 {
+            clips.run();
+            wraps.run();
+        }
+        for cpu in [&clips, &wraps] {
+            assert!(cpu.screen.get(0x3E, 0x1F) && cpu.screen.get(0x3F, 0x1F));
+        }
+        assert_eq!(clips.screen.0.iter().filter(|bit| **bit).count(), 2);
+        assert!(wraps.screen.get(0x00, 0x1F) && wraps.screen.get(0x3E, 0x00));
+        assert_eq!(wraps.screen.0.iter().filter(|bit| **bit).count(), 16);
+    }
+
+    #[test]
+    fn memory_opcodes() {
+        let rom = [
+            0x60, 0x9C, // 200: LD   V0, 9C
+            0xA3, 0x00, // 202: LD   I, 300
+            0xF0, 0x33, // 204: LD   B, V0
+            0xF2, 0x65, // 206: LD   V2, [I]
+            0xA3, 0x10, // 208: LD   I, 310
+            0xF2, 0x55, // 20A: LD   [I], V2
+            0xF1, 0x29, // 20C: LD   F, V1
+            0xF3, 0x0A, // 20E: LD   V3, K
+            0xFF, 0xFF, // 210: DW   FFFF
+        ];
+        let mut cpu = Cpu::load(&rom);
+        for _ in 0..7 {
+            cpu.run();
+        }
+        // 0x9C is 156
+        assert_eq!(cpu.registers[..3], [1, 5, 6]);
+        assert_eq!(cpu.memory[0x310..0x313], [1, 5, 6]);
+        assert_eq!(cpu.i, FONT_START + 5 * 5);
+        assert_eq!(cpu.memory[cpu.i..cpu.i + 5], [0xF0, 0x80, 0xF0, 0x10, 0xF0]);
+        // no key yet, so FX0A waits on itself
+        cpu.run();
+        assert_eq!(cpu.counter, 0x20E);
+        cpu.keys[0xB] = true;
+        cpu.run();
+        assert_eq!((cpu.registers[3], cpu.counter), (0xB, 0x210));
+        // undecodable opcodes stop the machine instead of taking the frontend down
+        cpu.run();
+        cpu.run();
+        assert_eq!(cpu.counter, 0x210);
+
+        let rom = [
+            0x60, 0x20, // 200: LD   V0, 20
+            0xA3, 0x00, // 202: LD   I, 300
+            0xF0, 0x1E, // 204: ADD  I, V0
+            0xF0, 0x15, // 206: LD   DT, V0
+            0x61, 0xFF, // 208: LD   V1, FF
+            0xE1, 0x9E, // 20A: SKP  V1, key F
+            0xE1, 0xA1, // 20C: SKNP V1
+        ];
+        let mut cpu = Cpu::load(&rom);
+        // a return address on the stack, FX1E and FX15 must not read it
+        cpu.stack[0] = 0x123;
+        cpu.keys[0xF] = true;
+        for _ in 0..4 {
+            cpu.run();
+        }
+        assert_eq!((cpu.i, cpu.delay), (0x320, 0x20));
+        // VX past the keypad only looks at its low nibble
+        cpu.run();
+        cpu.run();
+        assert_eq!(cpu.counter, 0x20E);
+        cpu.keys[0xF] = false;
+        cpu.counter = 0x20A;
+        cpu.run();
+        cpu.run();
+        assert_eq!(cpu.counter, 0x210);
+    }
+}
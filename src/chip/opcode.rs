@@ -0,0 +1,214 @@
+use std::fmt;
+
+/// A decoded opcode. Field names follow cowgod: x/y registers, kk byte, n nibble, addr nnn.
+/// Decoding never fails, anything we do not know about is kept as `Unknown`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Instruction {
+    Exit,                     // 0000 | Returns the program.
+    Cls,                      // 00E0 | Clears the screen.
+    Ret,                      // 00EE | Returns from a subroutine.
+    Sys(usize),               // 0NNN | Calls RCA 1802 program at address NNN.
+    Jp(usize),                // 1NNN | Jumps to address NNN.
+    Call(usize),              // 2NNN | Calls subroutine at address NNN.
+    SeK(usize, usize),        // 3XKK | Skips the next instruction if VX equals KK.
+    SneK(usize, usize),       // 4XKK | Skips the next instruction if VX doesn't equal KK.
+    Se(usize, usize),         // 5XY0 | Skips the next instruction if VX equals VY.
+    LdK(usize, usize),        // 6XKK | Sets VX to KK.
+    AddK(usize, usize),       // 7XKK | Adds KK to VX.
+    Ld(usize, usize),         // 8XY0 | Sets VX to the value of VY.
+    Or(usize, usize),         // 8XY1 | Sets VX to VX or VY.
+    And(usize, usize),        // 8XY2 | Sets VX to VX and VY.
+    Xor(usize, usize),        // 8XY3 | Sets VX to VX xor VY.
+    Add(usize, usize),        // 8XY4 | Adds VY to VX.
+    Sub(usize, usize),        // 8XY5 | VY is subtracted from VX.
+    Shr(usize, usize),        // 8XY6 | Shifts VX right by one.
+    Subn(usize, usize),       // 8XY7 | Sets VX to VY minus VX.
+    Shl(usize, usize),        // 8XYE | Shifts VX left by one.
+    Sne(usize, usize),        // 9XY0 | Skips the next instruction if VX doesn't equal VY.
+    LdI(usize),               // ANNN | Sets I to the address NNN.
+    JpV0(usize),              // BNNN | Jumps to the address NNN plus V0.
+    Rnd(usize, usize),        // CXKK | Sets VX to a random number and KK.
+    Drw(usize, usize, usize), // DXYN | Draws an 8xN sprite from I.
+    Skp(usize),   // EX9E | Skips the next instruction if the key stored in VX is pressed.
+    Sknp(usize),  // EXA1 | Skips the next instruction if the key stored in VX isn't pressed.
+    LdVDt(usize), // FX07 | Sets VX to the value of the delay timer.
+    LdKey(usize), // FX0A | A key press is awaited, and then stored in VX.
+    LdDtV(usize), // FX15 | Sets the delay timer to VX.
+    LdStV(usize), // FX18 | Sets the sound timer to VX.
+    AddIV(usize), // FX1E | Adds VX to I.
+    LdF(usize),   // FX29 | Sets I to the font sprite for VX.
+    LdB(usize),   // FX33 | Stores the BCD of VX at I, I+1, I+2.
+    LdIV(usize),  // FX55 | Stores V0 to VX in memory starting at address I.
+    LdVI(usize),  // FX65 | Fills V0 to VX from memory starting at address I.
+    Unknown(u16), // _ | Stops the machine on it.
+}
+impl Instruction {
+    pub fn decode(opcode: u16) -> Instruction {
+        let c = (((opcode & 0xF000) >> 12) as u8) as usize;
+        let x = (((opcode & 0x0F00) >> 8) as u8) as usize;
+        let y = (((opcode & 0x00F0) >> 4) as u8) as usize;
+        let n = ((opcode & 0x000F) as u8) as usize;
+        let kk = y << 4 | n;
+        let addr = (opcode & 0x0FFF) as usize;
+
+        match (c, x, y, n) {
+            // when in doubt, trust the cowgod
+            (0, 0, 0, 0) => Instruction::Exit,
+            (0x0, 0x0, 0xE, 0x0) => Instruction::Cls,
+            (0x0, 0x0, 0xE, 0xE) => Instruction::Ret,
+            (0x0, _, _, _) => Instruction::Sys(addr),
+            (0x1, _, _, _) => Instruction::Jp(addr),
+            (0x2, _, _, _) => Instruction::Call(addr),
+            (0x3, _, _, _) => Instruction::SeK(x, kk),
+            (0x4, _, _, _) => Instruction::SneK(x, kk),
+            (0x5, _, _, 0x0) => Instruction::Se(x, y),
+            (0x6, _, _, _) => Instruction::LdK(x, kk),
+            (0x7, _, _, _) => Instruction::AddK(x, kk),
+            (0x8, _, _, 0x0) => Instruction::Ld(x, y),
+            (0x8, _, _, 0x1) => Instruction::Or(x, y),
+            (0x8, _, _, 0x2) => Instruction::And(x, y),
+            (0x8, _, _, 0x3) => Instruction::Xor(x, y),
+            (0x8, _, _, 0x4) => Instruction::Add(x, y),
+            (0x8, _, _, 0x5) => Instruction::Sub(x, y),
+            (0x8, _, _, 0x6) => Instruction::Shr(x, y),
+            (0x8, _, _, 0x7) => Instruction::Subn(x, y),
+            (0x8, _, _, 0xE) => Instruction::Shl(x, y),
+            (0x9, _, _, 0x0) => Instruction::Sne(x, y),
+            (0xA, _, _, _) => Instruction::LdI(addr),
+            (0xB, _, _, _) => Instruction::JpV0(addr),
+            (0xC, _, _, _) => Instruction::Rnd(x, kk),
+            (0xD, _, _, _) => Instruction::Drw(x, y, n),
+            (0xE, _, 0x9, 0xE) => Instruction::Skp(x),
+            (0xE, _, 0xA, 0x1) => Instruction::Sknp(x),
+            (0xF, _, 0x0, 0x7) => Instruction::LdVDt(x),
+            (0xF, _, 0x0, 0xA) => Instruction::LdKey(x),
+            (0xF, _, 0x1, 0x5) => Instruction::LdDtV(x),
+            (0xF, _, 0x1, 0x8) => Instruction::LdStV(x),
+            (0xF, _, 0x1, 0xE) => Instruction::AddIV(x),
+            (0xF, _, 0x2, 0x9) => Instruction::LdF(x),
+            (0xF, _, 0x3, 0x3) => Instruction::LdB(x),
+            (0xF, _, 0x5, 0x5) => Instruction::LdIV(x),
+            (0xF, _, 0x6, 0x5) => Instruction::LdVI(x),
+            _ => Instruction::Unknown(opcode),
+        }
+    }
+    /// where control goes afterwards, without running anything
+    pub fn flow(&self) -> Flow {
+        match *self {
+            Instruction::Jp(addr) => Flow::Jump(addr),
+            Instruction::Call(addr) => Flow::Call(addr),
+            Instruction::Ret => Flow::Return,
+            Instruction::JpV0(addr) => Flow::Computed(addr),
+            Instruction::SeK(_, _)
+            | Instruction::SneK(_, _)
+            | Instruction::Se(_, _)
+            | Instruction::Sne(_, _)
+            | Instruction::Skp(_)
+            | Instruction::Sknp(_) => Flow::Skip,
+            Instruction::Exit | Instruction::Sys(_) | Instruction::Unknown(_) => Flow::Stop,
+            _ => Flow::Next,
+        }
+    }
+    /// the cowgod pattern this instruction was decoded from, e.g. `8XY4`
+    pub fn pattern(&self) -> &'static str {
+        match self {
+            Instruction::Exit => "0000",
+            Instruction::Cls => "00E0",
+            Instruction::Ret => "00EE",
+            Instruction::Sys(_) => "0NNN",
+            Instruction::Jp(_) => "1NNN",
+            Instruction::Call(_) => "2NNN",
+            Instruction::SeK(_, _) => "3XKK",
+            Instruction::SneK(_, _) => "4XKK",
+            Instruction::Se(_, _) => "5XY0",
+            Instruction::LdK(_, _) => "6XKK",
+            Instruction::AddK(_, _) => "7XKK",
+            Instruction::Ld(_, _) => "8XY0",
+            Instruction::Or(_, _) => "8XY1",
+            Instruction::And(_, _) => "8XY2",
+            Instruction::Xor(_, _) => "8XY3",
+            Instruction::Add(_, _) => "8XY4",
+            Instruction::Sub(_, _) => "8XY5",
+            Instruction::Shr(_, _) => "8XY6",
+            Instruction::Subn(_, _) => "8XY7",
+            Instruction::Shl(_, _) => "8XYE",
+            Instruction::Sne(_, _) => "9XY0",
+            Instruction::LdI(_) => "ANNN",
+            Instruction::JpV0(_) => "BNNN",
+            Instruction::Rnd(_, _) => "CXKK",
+            Instruction::Drw(_, _, _) => "DXYN",
+            Instruction::Skp(_) => "EX9E",
+            Instruction::Sknp(_) => "EXA1",
+            Instruction::LdVDt(_) => "FX07",
+            Instruction::LdKey(_) => "FX0A",
+            Instruction::LdDtV(_) => "FX15",
+            Instruction::LdStV(_) => "FX18",
+            Instruction::AddIV(_) => "FX1E",
+            Instruction::LdF(_) => "FX29",
+            Instruction::LdB(_) => "FX33",
+            Instruction::LdIV(_) => "FX55",
+            Instruction::LdVI(_) => "FX65",
+            Instruction::Unknown(_) => "????",
+        }
+    }
+}
+impl From<u16> for Instruction {
+    fn from(opcode: u16) -> Self {
+        Instruction::decode(opcode)
+    }
+}
+/// successors of an instruction at `addr`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Next,            // addr + 2
+    Skip,            // addr + 2 or addr + 4
+    Jump(usize),     // target
+    Call(usize),     // target, then addr + 2 once it returns
+    Return,          // whatever is on the stack
+    Computed(usize), // NNN + V0, not known without running
+    Stop,            // 0000, 0NNN and anything undecodable end the program here
+}
+/// cowgod style assembly, good enough for a disassembly pane
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Instruction::Exit => write!(f, "EXIT"),
+            Instruction::Cls => write!(f, "CLS"),
+            Instruction::Ret => write!(f, "RET"),
+            Instruction::Sys(addr) => write!(f, "SYS  {:03X}", addr),
+            Instruction::Jp(addr) => write!(f, "JP   {:03X}", addr),
+            Instruction::Call(addr) => write!(f, "CALL {:03X}", addr),
+            Instruction::SeK(x, kk) => write!(f, "SE   V{:X}, {:02X}", x, kk),
+            Instruction::SneK(x, kk) => write!(f, "SNE  V{:X}, {:02X}", x, kk),
+            Instruction::Se(x, y) => write!(f, "SE   V{:X}, V{:X}", x, y),
+            Instruction::LdK(x, kk) => write!(f, "LD   V{:X}, {:02X}", x, kk),
+            Instruction::AddK(x, kk) => write!(f, "ADD  V{:X}, {:02X}", x, kk),
+            Instruction::Ld(x, y) => write!(f, "LD   V{:X}, V{:X}", x, y),
+            Instruction::Or(x, y) => write!(f, "OR   V{:X}, V{:X}", x, y),
+            Instruction::And(x, y) => write!(f, "AND  V{:X}, V{:X}", x, y),
+            Instruction::Xor(x, y) => write!(f, "XOR  V{:X}, V{:X}", x, y),
+            Instruction::Add(x, y) => write!(f, "ADD  V{:X}, V{:X}", x, y),
+            Instruction::Sub(x, y) => write!(f, "SUB  V{:X}, V{:X}", x, y),
+            Instruction::Shr(x, y) => write!(f, "SHR  V{:X}, V{:X}", x, y),
+            Instruction::Subn(x, y) => write!(f, "SUBN V{:X}, V{:X}", x, y),
+            Instruction::Shl(x, y) => write!(f, "SHL  V{:X}, V{:X}", x, y),
+            Instruction::Sne(x, y) => write!(f, "SNE  V{:X}, V{:X}", x, y),
+            Instruction::LdI(addr) => write!(f, "LD   I, {:03X}", addr),
+            Instruction::JpV0(addr) => write!(f, "JP   V0, {:03X}", addr),
+            Instruction::Rnd(x, kk) => write!(f, "RND  V{:X}, {:02X}", x, kk),
+            Instruction::Drw(x, y, n) => write!(f, "DRW  V{:X}, V{:X}, {:X}", x, y, n),
+            Instruction::Skp(x) => write!(f, "SKP  V{:X}", x),
+            Instruction::Sknp(x) => write!(f, "SKNP V{:X}", x),
+            Instruction::LdVDt(x) => write!(f, "LD   V{:X}, DT", x),
+            Instruction::LdKey(x) => write!(f, "LD   V{:X}, K", x),
+            Instruction::LdDtV(x) => write!(f, "LD   DT, V{:X}", x),
+            Instruction::LdStV(x) => write!(f, "LD   ST, V{:X}", x),
+            Instruction::AddIV(x) => write!(f, "ADD  I, V{:X}", x),
+            Instruction::LdF(x) => write!(f, "LD   F, V{:X}", x),
+            Instruction::LdB(x) => write!(f, "LD   B, V{:X}", x),
+            Instruction::LdIV(x) => write!(f, "LD   [I], V{:X}", x),
+            Instruction::LdVI(x) => write!(f, "LD   V{:X}, [I]", x),
+            Instruction::Unknown(opcode) => write!(f, "DW   {:04X}", opcode),
+        }
+    }
+}
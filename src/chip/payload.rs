@@ -0,0 +1,142 @@
+use super::quirks::Profile;
+
+/// last bytes of an exported runner, the payload length sits right before it
+const MAGIC: &[u8; 8] = b"CHIPLOX\x01";
+const TRAILER: usize = 8 + MAGIC.len();
+
+/// everything an exported game needs besides the runner itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Payload {
+    /// empty runs the demo program
+    pub rom: Vec<u8>,
+    pub profile: Profile,
+    /// 0xRRGGBB for lit and unlit pixels
+    pub palette: (u32, u32),
+    /// host key for each chip key, `keymap[0xA]` is the key that presses A
+    pub keymap: [char; 16],
+}
+impl Default for Payload {
+    fn default() -> Self {
+        Payload {
+            rom: Vec::new(),
+            profile: Profile::Vip,
+            palette: (0xFFFFFF, 0x000000),
+            keymap: DEFAULT_KEYMAP,
+        }
+    }
+}
+///   1 2 3 C     1 2 3 4
+///   4 5 6 D  <- q w e r
+///   7 8 9 E     a s d f
+///   A 0 B F     z x c v
+pub const DEFAULT_KEYMAP: [char; 16] = [
+    'x', '1', '2', '3', 'q', 'w', 'e', 'a', 's', 'd', 'z', 'c', '4', 'r', 'f', 'v',
+];
+impl Payload {
+    /// chip key a host key presses, case insensitive
+    pub fn key(&self, c: char) -> Option<usize> {
+        let c = c.to_ascii_lowercase();
+        self.keymap
+            .iter()
+            .position(|key| key.to_ascii_lowercase() == c)
+    }
+    /// profile name, two colors, keymap, then the rom, all length prefixed where it matters
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let profile = self.profile.to_string();
+        let mut bytes = vec![profile.len() as u8];
+        bytes.extend(profile.as_bytes());
+        bytes.extend(self.palette.0.to_le_bytes());
+        bytes.extend(self.palette.1.to_le_bytes());
+        bytes.extend(self.keymap.iter().map(|key| *key as u8));
+        bytes.extend((self.rom.len() as u32).to_le_bytes());
+        bytes.extend(&self.rom);
+        bytes
+    }
+    pub fn from_bytes(bytes: &[u8]) -> Result<Payload, String> {
+        let mut reader = Reader(bytes);
+        let len = reader.take(1)?[0] as usize;
+        let profile = String::from_utf8_lossy(reader.take(len)?).parse()?;
+        let palette = (reader.u32()?, reader.u32()?);
+        let mut keymap = [' '; 16];
+        for (key, byte) in keymap.iter_mut().zip(reader.take(16)?) {
+            *key = *byte as char;
+        }
+        let len = reader.u32()? as usize;
+        let rom = reader.take(len)?.to_vec();
+        Ok(Payload {
+            rom,
+            profile,
+            palette,
+            keymap,
+        })
+    }
+    /// `runner` with this payload on the end, any payload it already had is replaced
+    pub fn append(&self, runner: &[u8]) -> Vec<u8> {
+        let mut exe = Payload::strip(runner).to_vec();
+        let payload = self.to_bytes();
+        exe.extend(&payload);
+        exe.extend((payload.len() as u64).to_le_bytes());
+        exe.extend(MAGIC);
+        exe
+    }
+    /// the payload on the end of an executable, None if there is none
+    pub fn find(exe: &[u8]) -> Option<Result<Payload, String>> {
+        let start = Payload::start(exe)?;
+        Some(Payload::from_bytes(&exe[start..exe.len() - TRAILER]))
+    }
+    /// the runner without its payload
+    pub fn strip(exe: &[u8]) -> &[u8] {
+        match Payload::start(exe) {
+            Some(start) => &exe[..start],
+            None => exe,
+        }
+    }
+    fn start(exe: &[u8]) -> Option<usize> {
+        if exe.len() < TRAILER || &exe[exe.len() - MAGIC.len()..] != MAGIC {
+            return None;
+        }
+        let mut len = [0; 8];
+        len.copy_from_slice(&exe[exe.len() - TRAILER..exe.len() - MAGIC.len()]);
+        (exe.len() - TRAILER).checked_sub(u64::from_le_bytes(len) as usize)
+    }
+}
+struct Reader<'a>(&'a [u8]);
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.0.len() < len {
+            return Err("payload is cut short".to_string());
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+    fn u32(&mut self) -> Result<u32, String> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.take(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appended_payload_round_trips() {
+        let payload = Payload {
+            rom: vec![0x12, 0x00],
+            profile: Profile::Schip,
+            palette: (0x33FF66, 0x101010),
+            ..Payload::default()
+        };
+        let runner = b"\x7fELF pretend runner".to_vec();
+        let exe = payload.append(&runner);
+        assert_eq!(Payload::find(&exe), Some(Ok(payload.clone())));
+        assert_eq!(Payload::strip(&exe), &runner[..]);
+        // exporting from an exported game swaps the payload instead of stacking them
+        let other = Payload::default();
+        assert_eq!(other.append(&exe), other.append(&runner));
+        assert_eq!(Payload::find(&runner), None);
+        assert_eq!(payload.key('Q'), Some(0x4));
+    }
+}
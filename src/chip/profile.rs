@@ -0,0 +1,190 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write,
+};
+
+use super::{clock::Tick, labels::Labels, opcode::Instruction, Cpu};
+
+/// counts where the cycles go, one instruction at a time
+/// calls are tracked from 2NNN/00EE so subroutines get inclusive totals
+#[derive(Debug, Clone)]
+pub struct Profiler {
+    pub cycles: u64,
+    pub per_address: Vec<u64>,
+    pub per_pattern: BTreeMap<&'static str, u64>,
+    /// subroutine address -> (calls, inclusive cycles)
+    pub subroutines: BTreeMap<usize, (u64, u64)>,
+    /// (subroutine address, cycles when it was entered)
+    frames: Vec<(usize, u64)>,
+    /// call stack -> cycles spent with exactly that stack, for flamegraphs
+    folded: HashMap<Vec<usize>, u64>,
+    /// cycles with the current stack not yet added to `folded`
+    pending: u64,
+}
+impl Default for Profiler {
+    fn default() -> Self {
+        Profiler {
+            cycles: 0,
+            per_address: vec![0; 4096],
+            per_pattern: BTreeMap::new(),
+            subroutines: BTreeMap::new(),
+            frames: Vec::new(),
+            folded: HashMap::new(),
+            pending: 0,
+        }
+    }
+}
+impl Profiler {
+    pub fn new() -> Profiler {
+        Profiler::default()
+    }
+    /// Cpu::advance, but every instruction is counted
+    pub fn advance(&mut self, cpu: &mut Cpu, tick: Tick) {
+        for _ in 0..tick.cycles {
+            self.run(cpu);
+        }
+        for _ in 0..tick.timers {
+            cpu.tick_timers();
+        }
+    }
+    /// Cpu::run, but counted
+    pub fn run(&mut self, cpu: &mut Cpu) {
+        let addr = cpu.counter;
+        let instruction = cpu.instruction_at(addr);
+        let depth = cpu.pointer;
+        cpu.run();
+        self.cycles += 1;
+        self.pending += 1;
+        if let Some(count) = self.per_address.get_mut(addr) {
+            *count += 1;
+        }
+        *self.per_pattern.entry(instruction.pattern()).or_insert(0) += 1;
+        match instruction {
+            // a call on a full stack stops on itself without entering anything
+            Instruction::Call(target) if cpu.pointer > depth => {
+                self.flush();
+                self.frames.push((target, self.cycles));
+                self.subroutines.entry(target).or_insert((0, 0)).0 += 1;
+            }
+            Instruction::Ret => {
+                self.flush();
+                // a ret with nothing on the stack just falls through, like Cpu::ret
+                if let Some((target, entered)) = self.frames.pop() {
+                    self.subroutines.entry(target).or_insert((0, 0)).1 += self.cycles - entered;
+                }
+            }
+            _ => (),
+        }
+    }
+    fn flush(&mut self) {
+        if self.pending > 0 {
+            let stack = self.frames.iter().map(|(target, _)| *target).collect();
+            *self.folded.entry(stack).or_insert(0) += self.pending;
+            self.pending = 0;
+        }
+    }
+    /// `start;sub_2A0;sub_300 1234` lines, the input flamegraph.pl and inferno expect
+    pub fn folded(&mut self, labels: &Labels) -> String {
+        self.flush();
+        let mut lines: Vec<String> = self
+            .folded
+            .iter()
+            .map(|(stack, cycles)| {
+                let mut line = String::from("start");
+                for target in stack {
+                    line.push(';');
+                    line.push_str(&labels.resolve(*target));
+                }
+                format!("{} {}", line, cycles)
+            })
+            .collect();
+        lines.sort();
+        lines.join("\n") + "\n"
+    }
+    /// hottest addresses, instruction kinds and subroutines
+    pub fn report(&mut self, cpu: &Cpu, labels: &Labels, top: usize) -> String {
+        self.flush();
+        let total = self.cycles.max(1) as f64;
+        let percent = |count: u64| count as f64 * 100. / total;
+        let mut out = String::new();
+        let _ = writeln!(out, "{} cycles\n", self.cycles);
+
+        let _ = writeln!(out, "hot addresses");
+        let mut addresses: Vec<(usize, u64)> = self
+            .per_address
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(addr, count)| (addr, *count))
+            .collect();
+        addresses.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        for (addr, count) in addresses.iter().take(top) {
+            let _ = writeln!(
+                out,
+                "  {:03X} {:>10} {:6.2}%  {:<16} {}",
+                addr,
+                count,
+                percent(*count),
+                cpu.instruction_at(*addr).to_string(),
+                labels.resolve(*addr)
+            );
+        }
+
+        let _ = writeln!(out, "\ninstructions");
+        let mut patterns: Vec<(&&str, &u64)> = self.per_pattern.iter().collect();
+        patterns.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        for (pattern, count) in patterns {
+            let _ = writeln!(out, "  {} {:>10} {:6.2}%", pattern, count, percent(*count));
+        }
+
+        let _ = writeln!(out, "\nsubroutines (inclusive)");
+        let mut subroutines: Vec<(&usize, &(u64, u64))> = self.subroutines.iter().collect();
+        subroutines.sort_by(|a, b| (b.1).1.cmp(&(a.1).1).then(a.0.cmp(b.0)));
+        for (target, (calls, cycles)) in subroutines.iter().take(top) {
+            let _ = writeln!(
+                out,
+                "  {:<12} {:>8} calls {:>10} {:6.2}%",
+                labels.resolve(**target),
+                calls,
+                cycles,
+                percent(*cycles)
+            );
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_stack_does_not_grow_frames() {
+        let mut cpu = Cpu::load(&[
+            0x22, 0x00, // 200: CALL 200
+        ]);
+        let mut profiler = Profiler::new();
+        for _ in 0..cpu.stack.len() + 100 {
+            profiler.run(&mut cpu);
+        }
+        assert_eq!(cpu.pointer, cpu.stack.len());
+        assert_eq!(profiler.frames.len(), cpu.stack.len());
+        assert_eq!(profiler.subroutines[&0x200].0, cpu.stack.len() as u64);
+    }
+
+    #[test]
+    fn counts_calls_and_folds_stacks() {
+        // 200: call 206, 202: call 206, 204: jp 204, 206: add v0 1, 208: ret
+        let mut cpu = Cpu::load(&[0x22, 0x06, 0x22, 0x06, 0x12, 0x04, 0x70, 0x01, 0x00, 0xEE]);
+        let mut profiler = Profiler::new();
+        for _ in 0..8 {
+            profiler.run(&mut cpu);
+        }
+        assert_eq!(profiler.cycles, 8);
+        assert_eq!(profiler.per_address[0x204], 2);
+        assert_eq!(profiler.per_pattern["2NNN"], 2);
+        assert_eq!(profiler.subroutines[&0x206], (2, 4));
+        let labels = Labels::scan(&cpu.memory, cpu.entry());
+        assert_eq!(profiler.folded(&labels), "start 4\nstart;sub_206 4\n");
+    }
+}
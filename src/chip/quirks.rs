@@ -0,0 +1,73 @@
+use std::{fmt, str::FromStr};
+
+/// interpreters disagree on a handful of opcodes, a profile names one set of answers
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Profile {
+    /// the original cosmac vip chip-8
+    Vip,
+    /// superchip 1.1, what most modern roms are tested on
+    Schip,
+    /// octo's xo-chip
+    XoChip,
+}
+/// how a profile answers each disputed opcode
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Quirks {
+    /// 8XY6/8XYE shift VY into VX, instead of shifting VX in place
+    pub shift_uses_vy: bool,
+    /// FX55/FX65 leave I pointing past the last register
+    pub load_store_moves_i: bool,
+    /// BNNN jumps to NNN + VX (BXNN) instead of NNN + V0
+    pub jump_uses_vx: bool,
+    /// 8XY1/8XY2/8XY3 reset VF
+    pub logic_resets_vf: bool,
+    /// DXYN clips at the screen edge instead of wrapping
+    pub draw_clips: bool,
+}
+impl Profile {
+    pub fn quirks(&self) -> Quirks {
+        match self {
+            Profile::Vip => Quirks {
+                shift_uses_vy: true,
+                load_store_moves_i: true,
+                jump_uses_vx: false,
+                logic_resets_vf: true,
+                draw_clips: true,
+            },
+            Profile::Schip => Quirks {
+                shift_uses_vy: false,
+                load_store_moves_i: false,
+                jump_uses_vx: true,
+                logic_resets_vf: false,
+                draw_clips: true,
+            },
+            Profile::XoChip => Quirks {
+                shift_uses_vy: true,
+                load_store_moves_i: true,
+                jump_uses_vx: false,
+                logic_resets_vf: false,
+                draw_clips: false,
+            },
+        }
+    }
+}
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Profile::Vip => "vip",
+            Profile::Schip => "schip",
+            Profile::XoChip => "xochip",
+        })
+    }
+}
+impl FromStr for Profile {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "vip" | "chip8" | "chip-8" => Ok(Profile::Vip),
+            "schip" | "superchip" => Ok(Profile::Schip),
+            "xochip" | "xo-chip" => Ok(Profile::XoChip),
+            _ => Err(format!("unknown quirk profile: {}", s)),
+        }
+    }
+}
@@ -0,0 +1,29 @@
+pub const SCREEN_X: usize = 64;
+pub const SCREEN_Y: usize = 32;
+pub const PIXELS: usize = SCREEN_X * SCREEN_Y;
+/// headless framebuffer, one bool per pixel, row major
+/// frontends (bevy, terminal) read this and draw however they like
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Screen(pub [bool; PIXELS]);
+impl Default for Screen {
+    fn default() -> Self {
+        Screen([false; PIXELS])
+    }
+}
+impl Screen {
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.0[x % SCREEN_X + (y % SCREEN_Y) * SCREEN_X]
+    }
+    /// one color per pixel, row major, what a texture or canvas upload wants
+    pub fn fill_bitmap(&self, on: u32, off: u32, bitmap: &mut [u32]) {
+        for (color, bit) in bitmap.iter_mut().zip(self.0.iter()) {
+            *color = if *bit { on } else { off };
+        }
+    }
+    pub(crate) fn flp(&mut self, i: usize) {
+        self.0[i] = !self.0[i];
+    }
+    pub(crate) fn clr(&mut self) {
+        self.0 = [false; PIXELS];
+    }
+}
@@ -0,0 +1,125 @@
+use std::fmt::Write;
+
+/// chip sprites are 8 wide and 1-15 rows, schip adds 16x16
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sprite {
+    pub width: usize,
+    pub rows: usize,
+    pub pixels: Vec<bool>,
+}
+impl Default for Sprite {
+    fn default() -> Self {
+        Sprite::new(8, 5)
+    }
+}
+impl Sprite {
+    pub fn new(width: usize, rows: usize) -> Sprite {
+        Sprite {
+            width,
+            rows,
+            pixels: vec![false; width * rows],
+        }
+    }
+    /// bytes per row, 1 for chip, 2 for schip
+    fn stride(&self) -> usize {
+        self.width.div_ceil(8)
+    }
+    pub fn get(&self, x: usize, y: usize) -> bool {
+        self.pixels[x + y * self.width]
+    }
+    pub fn flp(&mut self, x: usize, y: usize) {
+        let i = x + y * self.width;
+        self.pixels[i] = !self.pixels[i];
+    }
+    /// keeps whatever still fits, new rows start empty
+    pub fn resize(&mut self, width: usize, rows: usize) {
+        let mut resized = Sprite::new(width, rows);
+        for y in 0..rows.min(self.rows) {
+            for x in 0..width.min(self.width) {
+                resized.pixels[x + y * width] = self.get(x, y);
+            }
+        }
+        *self = resized;
+    }
+    /// msb is the leftmost pixel, the same order DXYN reads them
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![0u8; self.stride() * self.rows];
+        for y in 0..self.rows {
+            for x in 0..self.width {
+                if self.get(x, y) {
+                    bytes[y * self.stride() + x / 8] |= 0x80 >> (x % 8);
+                }
+            }
+        }
+        bytes
+    }
+    pub fn from_bytes(width: usize, bytes: &[u8]) -> Sprite {
+        let stride = width.div_ceil(8);
+        let mut sprite = Sprite::new(width, bytes.len() / stride);
+        for y in 0..sprite.rows {
+            for x in 0..width {
+                sprite.pixels[x + y * width] = bytes[y * stride + x / 8] & (0x80 >> (x % 8)) != 0;
+            }
+        }
+        sprite
+    }
+    /// one `db` per row, with the row drawn in a comment
+    pub fn to_db(&self) -> String {
+        let mut out = String::new();
+        for row in self.to_bytes().chunks(self.stride()) {
+            let bytes: Vec<String> = row.iter().map(|byte| format!("0x{:02X}", byte)).collect();
+            let picture: String = row
+                .iter()
+                .map(|byte| format!("{:08b}", byte))
+                .collect::<String>()
+                .chars()
+                .map(|bit| if bit == '1' { '#' } else { '.' })
+                .collect();
+            let _ = writeln!(out, "db {} ; {}", bytes.join(", "), picture);
+        }
+        out
+    }
+    pub fn to_hex(&self) -> String {
+        self.to_bytes()
+            .iter()
+            .map(|byte| format!("{:02X}", byte))
+            .collect()
+    }
+    /// binary literals read like the picture
+    pub fn to_chiplox(&self, name: &str) -> String {
+        let mut out = format!("{} = [\n", name);
+        for row in self.to_bytes().chunks(self.stride()) {
+            let bits: String = row.iter().map(|byte| format!("{:08b}", byte)).collect();
+            let _ = writeln!(out, "    0b{},", bits);
+        }
+        out.push(']');
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes_round_trip() {
+        let zero = [0xF0, 0x90, 0x90, 0x90, 0xF0];
+        assert_eq!(Sprite::from_bytes(8, &zero).to_bytes(), zero);
+        let wide = [0x80, 0x01, 0xFF, 0x00];
+        let sprite = Sprite::from_bytes(16, &wide);
+        assert_eq!(sprite.rows, 2);
+        assert!(sprite.get(0, 0) && sprite.get(15, 0) && !sprite.get(1, 0));
+        assert_eq!(sprite.to_bytes(), wide);
+    }
+
+    #[test]
+    fn exports() {
+        let sprite = Sprite::from_bytes(8, &[0xF0, 0x90]);
+        assert_eq!(sprite.to_hex(), "F090");
+        assert_eq!(sprite.to_db(), "db 0xF0 ; ####....\ndb 0x90 ; #..#....\n");
+        assert_eq!(
+            sprite.to_chiplox("zero"),
+            "zero = [\n    0b11110000,\n    0b10010000,\n]"
+        );
+    }
+}
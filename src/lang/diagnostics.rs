@@ -0,0 +1,452 @@
+// errors and warnings from every stage of lang
+// rendered as codespan style source snippets for people, or as json for tools (the ide, editors)
+use std::{collections::BTreeMap, fmt::Write};
+
+use super::span::Span;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const BLUE: &str = "\x1b[1;34m";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Help,
+    Note,
+    Warning,
+    Error,
+}
+impl Severity {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Help => "help",
+            Severity::Note => "note",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+    fn color(&self) -> &'static str {
+        match self {
+            Severity::Help => "\x1b[1;36m",
+            Severity::Note => "\x1b[1;32m",
+            Severity::Warning => "\x1b[1;33m",
+            Severity::Error => "\x1b[1;31m",
+        }
+    }
+}
+/// everything lang can report, codes never change meaning so docs and tools can rely on them
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Code {
+    UnknownCharacter,
+    UnterminatedString,
+    UnterminatedComment,
+    InvalidEscape,
+    InvalidNumber,
+    NumberOverflow,
+    ExpectedExpression,
+    UnexpectedToken,
+    UnclosedDelimiter,
+    ExpectedToken,
+    UnclosedBlock,
+    DanglingDocComment,
+}
+impl Code {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Code::UnknownCharacter => "E0001",
+            Code::UnterminatedString => "E0002",
+            Code::UnterminatedComment => "E0003",
+            Code::InvalidEscape => "E0004",
+            Code::InvalidNumber => "E0005",
+            Code::NumberOverflow => "E0006",
+            Code::ExpectedExpression => "E0100",
+            Code::UnexpectedToken => "E0101",
+            Code::UnclosedDelimiter => "E0102",
+            Code::ExpectedToken => "E0103",
+            Code::UnclosedBlock => "E0104",
+            Code::DanglingDocComment => "W0001",
+        }
+    }
+}
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub span: Span,
+    pub message: String,
+    /// what the diagnostic is about, secondary labels add context
+    pub primary: bool,
+}
+/// text that would fix the problem if it replaced `span`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Suggestion {
+    pub span: Span,
+    pub replacement: String,
+    pub message: String,
+}
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub code: Code,
+    pub severity: Severity,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+    pub suggestions: Vec<Suggestion>,
+}
+impl Diagnostic {
+    pub fn new(severity: Severity, code: Code, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            code,
+            severity,
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+            suggestions: Vec::new(),
+        }
+    }
+    pub fn error(code: Code, message: impl Into<String>) -> Diagnostic {
+        Diagnostic::new(Severity::Error, code, message)
+    }
+    pub fn warning(code: Code, message: impl Into<String>) -> Diagnostic {
+        Diagnostic::new(Severity::Warning, code, message)
+    }
+    pub fn label(mut self, span: Span, message: impl Into<String>) -> Diagnostic {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+            primary: true,
+        });
+        self
+    }
+    pub fn secondary(mut self, span: Span, message: impl Into<String>) -> Diagnostic {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+            primary: false,
+        });
+        self
+    }
+    pub fn note(mut self, note: impl Into<String>) -> Diagnostic {
+        self.notes.push(note.into());
+        self
+    }
+    pub fn suggest(
+        mut self,
+        span: Span,
+        replacement: impl Into<String>,
+        message: impl Into<String>,
+    ) -> Diagnostic {
+        self.suggestions.push(Suggestion {
+            span,
+            replacement: replacement.into(),
+            message: message.into(),
+        });
+        self
+    }
+    /// where the diagnostic points, the first primary label
+    pub fn span(&self) -> Option<Span> {
+        self.labels
+            .iter()
+            .find(|label| label.primary)
+            .or_else(|| self.labels.first())
+            .map(|label| label.span)
+    }
+    /// error[E0002]: message
+    ///  --> name:line:col
+    ///   |
+    /// 1 | source line
+    ///   |     ^^^ primary label
+    ///   = note: ...
+    pub fn render(&self, source: &str, name: &str, color: bool) -> String {
+        let paint = |style: &'static str, text: &str| {
+            if color {
+                format!("{}{}{}", style, text, RESET)
+            } else {
+                text.to_string()
+            }
+        };
+        let mut out = format!(
+            "{}{}\n",
+            paint(
+                self.severity.color(),
+                &format!("{}[{}]", self.severity.name(), self.code.as_str())
+            ),
+            paint(BOLD, &format!(": {}", self.message))
+        );
+        let width = self
+            .labels
+            .iter()
+            .map(|label| label.span.line)
+            .chain(
+                self.suggestions
+                    .iter()
+                    .map(|suggestion| suggestion.span.line),
+            )
+            .max()
+            .unwrap_or(0)
+            .to_string()
+            .len();
+        let pad = " ".repeat(width);
+        let gutter = paint(BLUE, &format!("{} |", pad));
+        if let Some(span) = self.span() {
+            let _ = writeln!(out, "{}{} {}:{}", pad, paint(BLUE, "-->"), name, span);
+        }
+        let mut lines: BTreeMap<usize, Vec<&Label>> = BTreeMap::new();
+        for label in &self.labels {
+            lines.entry(label.span.line).or_default().push(label);
+        }
+        if !lines.is_empty() {
+            let _ = writeln!(out, "{}", gutter);
+        }
+        let mut previous = None;
+        for (line, labels) in lines.iter_mut() {
+            if previous.is_some_and(|previous| line - previous > 1) {
+                let _ = writeln!(out, "{}", paint(BLUE, "..."));
+            }
+            previous = Some(*line);
+            let text = source_line(source, *line);
+            let _ = writeln!(
+                out,
+                "{} {}",
+                paint(BLUE, &format!("{:>width$} |", line, width = width)),
+                text
+            );
+            labels.sort_by_key(|label| (label.span.col, !label.primary));
+            for label in labels.iter() {
+                let marks = underline(&text, &label.span, if label.primary { '^' } else { '-' });
+                let style = if label.primary {
+                    self.severity.color()
+                } else {
+                    BLUE
+                };
+                let _ = writeln!(
+                    out,
+                    "{} {}{}",
+                    gutter,
+                    " ".repeat(label.span.col - 1),
+                    paint(style, format!("{} {}", marks, label.message).trim_end())
+                );
+            }
+        }
+        for note in &self.notes {
+            let _ = writeln!(out, "{} {} {}", pad, paint(BOLD, "= note:"), note);
+        }
+        for suggestion in &self.suggestions {
+            let _ = writeln!(
+                out,
+                "{} {} {}",
+                pad,
+                paint(Severity::Help.color(), "= help:"),
+                suggestion.message
+            );
+            // the line as it would read with the replacement spliced in
+            let start = source[..suggestion.span.start]
+                .rfind('\n')
+                .map_or(0, |newline| newline + 1);
+            let end = source[suggestion.span.end..]
+                .find('\n')
+                .map_or(source.len(), |newline| suggestion.span.end + newline);
+            let _ = writeln!(
+                out,
+                "{} {}{}{}",
+                paint(
+                    BLUE,
+                    &format!("{:>width$} |", suggestion.span.line, width = width)
+                ),
+                &source[start..suggestion.span.start],
+                paint(Severity::Help.color(), &suggestion.replacement),
+                source[suggestion.span.end..end].trim_end_matches('\r')
+            );
+        }
+        out
+    }
+    /// one object, spans carry both byte offsets and line/col so tools can use either
+    pub fn to_json(&self) -> String {
+        let labels: Vec<String> = self
+            .labels
+            .iter()
+            .map(|label| {
+                format!(
+                    "{{{},\"message\":{},\"primary\":{}}}",
+                    span_json(&label.span),
+                    json_string(&label.message),
+                    label.primary
+                )
+            })
+            .collect();
+        let notes: Vec<String> = self.notes.iter().map(|note| json_string(note)).collect();
+        let suggestions: Vec<String> = self
+            .suggestions
+            .iter()
+            .map(|suggestion| {
+                format!(
+                    "{{{},\"replacement\":{},\"message\":{}}}",
+                    span_json(&suggestion.span),
+                    json_string(&suggestion.replacement),
+                    json_string(&suggestion.message)
+                )
+            })
+            .collect();
+        format!(
+            "{{\"code\":\"{}\",\"severity\":\"{}\",\"message\":{},\"labels\":[{}],\"notes\":[{}],\"suggestions\":[{}]}}",
+            self.code.as_str(),
+            self.severity.name(),
+            json_string(&self.message),
+            labels.join(","),
+            notes.join(","),
+            suggestions.join(",")
+        )
+    }
+}
+/// everything one run of the lexer and parser had to say, in the order it was found
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Diagnostics(pub Vec<Diagnostic>);
+impl Diagnostics {
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.0.push(diagnostic);
+    }
+    pub fn extend(&mut self, diagnostics: Diagnostics) {
+        self.0.extend(diagnostics.0);
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+    pub fn has_errors(&self) -> bool {
+        self.0
+            .iter()
+            .any(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+    pub fn render(&self, source: &str, name: &str, color: bool) -> String {
+        self.0
+            .iter()
+            .map(|diagnostic| diagnostic.render(source, name, color))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+    pub fn to_json(&self) -> String {
+        let diagnostics: Vec<String> = self.0.iter().map(Diagnostic::to_json).collect();
+        format!("[{}]", diagnostics.join(","))
+    }
+}
+/// 1 based, tabs become one space so columns line up with the underline
+fn source_line(source: &str, line: usize) -> String {
+    source
+        .split('\n')
+        .nth(line - 1)
+        .unwrap_or("")
+        .trim_end_matches('\r')
+        .replace('\t', " ")
+}
+/// one mark per char of the span on this line, at least one so empty spans still show
+fn underline(line: &str, span: &Span, mark: char) -> String {
+    let rest = line.chars().count().saturating_sub(span.col - 1);
+    let len = line
+        .chars()
+        .skip(span.col - 1)
+        .take(rest)
+        .scan(0, |bytes, c| {
+            *bytes += c.len_utf8();
+            Some(*bytes)
+        })
+        .take_while(|bytes| *bytes <= span.len())
+        .count();
+    mark.to_string().repeat(len.max(1))
+}
+fn span_json(span: &Span) -> String {
+    format!(
+        "\"start\":{},\"end\":{},\"line\":{},\"col\":{}",
+        span.start, span.end, span.line, span.col
+    )
+}
+fn json_string(text: &str) -> String {
+    let mut out = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", c as u32);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_snippets_and_json() {
+        let source = "x = 1\ny = \"hi";
+        let diagnostic = Diagnostic::error(Code::UnterminatedString, "unterminated string")
+            .label(Span::new(source, 10..13), "this string is never closed")
+            .secondary(Span::new(source, 0..1), "unrelated context")
+            .note("strings end with `\"`")
+            .suggest(Span::new(source, 13..13), "\"", "close it");
+        assert_eq!(
+            diagnostic.render(source, "repl", false),
+            [
+                "error[E0002]: unterminated string",
+                " --> repl:2:5",
+                "  |",
+                "1 | x = 1",
+                "  | - unrelated context",
+                "2 | y = \"hi",
+                "  |     ^^^ this string is never closed",
+                "  = note: strings end with `\"`",
+                "  = help: close it",
+                "2 | y = \"hi\"",
+                "",
+            ]
+            .join("\n")
+        );
+        assert_eq!(
+            Diagnostics(vec![diagnostic]).to_json(),
+            concat!(
+                r#"[{"code":"E0002","severity":"error","message":"unterminated string","#,
+                r#""labels":[{"start":10,"end":13,"line":2,"col":5,"message":"this string is never closed","primary":true},"#,
+                r#"{"start":0,"end":1,"line":1,"col":1,"message":"unrelated context","primary":false}],"#,
+                r#""notes":["strings end with `\"`"],"#,
+                r#""suggestions":[{"start":13,"end":13,"line":2,"col":8,"replacement":"\"","message":"close it"}]}]"#
+            )
+        );
+    }
+
+    #[test]
+    fn tokenizer_reports_what_it_skips() {
+        let source = "x @# 1\n\"never closed";
+        let mut diagnostics = Diagnostics::default();
+        let tokens = crate::lang::tokens::tokenize_with(source, &mut diagnostics);
+        assert_eq!(tokens.0.len(), 3);
+        let found: Vec<(&str, &str, Option<Span>)> = diagnostics
+            .0
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.code.as_str(),
+                    &diagnostic.message[..],
+                    diagnostic.span(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "E0001",
+                    "unknown characters `@#`",
+                    Some(Span::new(source, 2..4))
+                ),
+                (
+                    "E0002",
+                    "unterminated string",
+                    Some(Span::new(source, 7..8))
+                ),
+            ]
+        );
+        assert!(diagnostics.has_errors());
+    }
+}
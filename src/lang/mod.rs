@@ -1,3 +1,5 @@
+pub mod diagnostics;
 pub mod repl;
+pub mod span;
 pub mod syntax;
 mod tokens;
@@ -1,3 +1,63 @@
 pub mod syntax;
 pub mod token;
 pub mod tree;
+
+use std::io::{self, IsTerminal, Write};
+
+use super::{
+    diagnostics::Diagnostics,
+    span::Spanned,
+    tokens::{Checkpoint, Lexer, Token, Tokens},
+};
+
+/// reads lines until they make a whole input, an open string, a trailing `\` or a doc comment asks for another line
+/// None once stdin is closed with nothing read
+pub(crate) fn read(prompt: &str) -> Option<(String, Tokens, Diagnostics)> {
+    let mut source = String::new();
+    let mut tokens = Vec::new();
+    let mut diagnostics = Diagnostics::default();
+    let mut checkpoint = Checkpoint::default();
+    print!("{}> ", prompt);
+    loop {
+        io::stdout().flush().unwrap();
+        let read = io::stdin()
+            .read_line(&mut source)
+            .expect("error: unable to read user input");
+        let mut lexer = Lexer::resume(&source, checkpoint);
+        if read > 0 {
+            lexer = lexer.partial();
+        }
+        tokens.extend(lexer.by_ref());
+        diagnostics.extend(lexer.take_diagnostics());
+        // a doc comment waits for the declaration it documents
+        let documenting = tokens
+            .last()
+            .is_some_and(|token: &Spanned<Token>| matches!(token.node, Token::DocComment(_)));
+        match lexer.incomplete() {
+            Some(resume) if read > 0 => {
+                checkpoint = resume;
+                print!("{}> ", ".".repeat(prompt.len()));
+            }
+            None if read > 0 && documenting => {
+                checkpoint = lexer.checkpoint();
+                print!("{}> ", ".".repeat(prompt.len()));
+            }
+            _ if source.is_empty() => return None,
+            _ => return Some((source, Tokens(tokens), diagnostics)),
+        }
+    }
+}
+
+/// what a repl prints after each line, json with `--json` for tools, colored snippets on a terminal
+pub(crate) fn report(source: &str, diagnostics: &Diagnostics) -> String {
+    if std::env::args().any(|arg| arg == "--json") {
+        format!("{}\n", diagnostics.to_json())
+    } else if diagnostics.is_empty() {
+        String::new()
+    } else {
+        format!(
+            "\n{}",
+            diagnostics.render(source, "repl", std::io::stdout().is_terminal())
+        )
+    }
+}
@@ -1 +1,22 @@
+use super::report;
+use crate::lang::syntax::cst::SyntaxNode;
 
+use std::io::{self, Write};
+
+/// prints the lossless tree for each line, trivia and all
+pub fn repl() -> ! {
+    loop {
+        print!("Syntax> ");
+        io::stdout().flush().unwrap();
+        let mut input = String::new();
+        if io::stdin()
+            .read_line(&mut input)
+            .expect("error: unable to read user input")
+            == 0
+        {
+            std::process::exit(0)
+        }
+        let (root, diagnostics) = SyntaxNode::parse(&input);
+        print!("\nSyntax:\n{:?}{}\n", root, report(&input, &diagnostics));
+    }
+}
@@ -1,7 +1,7 @@
-use std::io;
-use std::io::Write;
-
-use super::super::tokens::{tokenize, Tokens};
+use super::{
+    super::tokens::{tokenize, Tokens},
+    read, report,
+};
 
 pub fn new(input: &str) -> Tokens {
     // cli input arguments (clap?)
@@ -17,21 +17,15 @@ pub fn new(input: &str) -> Tokens {
     tokenize(input)
 }
 
-fn rep(input: &str) -> Tokens {
-    new(input)
-}
-
 pub fn repl() -> ! {
-    std::io::stdout().flush().unwrap();
-    print!("Token> ");
     loop {
-        let mut input = String::new();
-        std::io::stdout().flush().unwrap();
-        io::stdin()
-            .read_line(&mut input)
-            .expect("error: unable to read user input");
-        std::io::stdout().flush().unwrap();
-        print!("\nToken:\n{:#?}\n\nToken> ", rep(&input).0);
-        std::io::stdout().flush().unwrap();
+        let Some((input, tokens, diagnostics)) = read("Token") else {
+            std::process::exit(0)
+        };
+        print!(
+            "\nToken:\n{:#?}\n{}\n",
+            tokens.0,
+            report(&input, &diagnostics)
+        );
     }
 }
@@ -1,9 +1,9 @@
-use std::io;
-use std::io::Write;
+use std::collections::HashMap;
 
+use super::{read, report};
 use crate::lang::{syntax::trees::Tree, tokens::Tokens};
 
-pub fn new(tokens: Tokens) -> Tree {
+pub fn new(source: &str, tokens: Tokens) -> Tree {
     // cli input arguments (clap?)
     // <binary> <file descriptor> <file descriptor> ...
     // run each sequentially, stop on first error
@@ -14,24 +14,25 @@ pub fn new(tokens: Tokens) -> Tree {
     // repl ignores newlines escaped with '\'
     // todo: implement
     // todo: flags
-    Tree::new(tokens)
-}
-
-fn rep(tokens: Tokens) -> Tree {
-    new(tokens)
+    Tree::new(source, tokens)
 }
 
+/// `:doc name` shows the /// comments of anything declared earlier in the session
 pub fn repl() -> ! {
-    std::io::stdout().flush().unwrap();
-    print!("Tree> ");
+    let mut docs = HashMap::new();
     loop {
-        let mut input = String::new();
-        std::io::stdout().flush().unwrap();
-        io::stdin()
-            .read_line(&mut input)
-            .expect("error: unable to read user input");
-        std::io::stdout().flush().unwrap();
-        print!("\nTree:\n{:#?}\n\nTree> ", rep(super::token::new(&input)));
-        std::io::stdout().flush().unwrap();
+        let Some((input, tokens, mut diagnostics)) = read("Tree") else {
+            std::process::exit(0)
+        };
+        if let Some(name) = input.trim().strip_prefix(":doc") {
+            match docs.get(name.trim()) {
+                Some(doc) => println!("{}", doc),
+                None => println!("no docs for `{}`", name.trim()),
+            }
+            continue;
+        }
+        let tree = Tree::parse(&input, tokens, &mut diagnostics);
+        docs.extend(tree.docs());
+        print!("\nTree:\n{:#?}\n{}\n", tree, report(&input, &diagnostics));
     }
 }
@@ -0,0 +1,87 @@
+use std::{fmt, ops::Range};
+
+/// where something came from in the source, a byte range plus the 1 based line and column it starts at
+/// columns count chars, not bytes, so they match what an editor shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub col: usize,
+}
+impl Span {
+    pub fn new(source: &str, range: Range<usize>) -> Span {
+        let before = &source[..range.start];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        Span {
+            start: range.start,
+            end: range.end,
+            line: before.matches('\n').count() + 1,
+            col: before[line_start..].chars().count() + 1,
+        }
+    }
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+    /// nothing from the source, e.g. a leaf the parser made up
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+    /// from the start of self to the end of other
+    pub fn to(&self, other: Span) -> Span {
+        Span {
+            end: other.end.max(self.end),
+            ..*self
+        }
+    }
+}
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.line, self.col)
+    }
+}
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    pub node: T,
+    pub span: Span,
+}
+impl<T> Spanned<T> {
+    pub fn new(node: T, span: Span) -> Spanned<T> {
+        Spanned { node, span }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::tokens::{tokenize, Token};
+
+    #[test]
+    fn tokens_point_at_their_source() {
+        let source = "x = 1\n  \"é\" + 22.5";
+        let tokens = tokenize(source);
+        let found: Vec<(&str, usize, usize)> = tokens
+            .0
+            .iter()
+            .map(|token| (&source[token.span.range()], token.span.line, token.span.col))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("x", 1, 1),
+                ("=", 1, 3),
+                ("1", 1, 5),
+                ("\"é\"", 2, 3),
+                ("+", 2, 7),
+                ("22.5", 2, 9)
+            ]
+        );
+        assert!(matches!(tokens.0[4].node, Token::Plus(_)));
+        // é is two bytes but one column
+        assert_eq!(Span::new(source, 9..11).to_string(), "2:4");
+        assert_eq!(Span::new(source, 12..13).to_string(), "2:6");
+    }
+}
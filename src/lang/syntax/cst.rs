@@ -0,0 +1,597 @@
+// lossless concrete syntax tree, green/red style
+// green nodes are immutable text with widths and can be shared or rebuilt by a formatter,
+// red nodes put a green node at an offset under a parent so tools can walk up and down
+// every byte of the source is in exactly one token or its trivia, so printing the root gives the input back
+use std::{fmt, ops::Range, rc::Rc};
+
+use crate::lang::{
+    diagnostics::{Code, Diagnostic, Diagnostics},
+    span::{Span, Spanned},
+    tokens::{Lexer, Token},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    Number,
+    Identifier,
+    Keyword,
+    String,
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+    Modulo,
+    LParen,
+    RParen,
+    Dot,
+    Colon,
+    Less,
+    Greater,
+    LBracket,
+    RBracket,
+    Comma,
+    Question,
+    Caret,
+    Ampersand,
+    Pipe,
+    Tilde,
+    NotEqual,
+    EqualEqual,
+    LessEqual,
+    GreaterEqual,
+    Equal,
+    PlusPlus,
+    /// holds whatever trivia is left after the last token
+    Eof,
+    Whitespace,
+    Newline,
+    Comment,
+    DocComment,
+    Unknown,
+    Root,
+    /// one line of tokens, a group can carry it over line breaks
+    Line,
+    /// ( ... ), closing paren included when there is one
+    Group,
+}
+impl SyntaxKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(
+            self,
+            SyntaxKind::Whitespace
+                | SyntaxKind::Newline
+                | SyntaxKind::Comment
+                | SyntaxKind::DocComment
+                | SyntaxKind::Unknown
+        )
+    }
+}
+impl From<&Token> for SyntaxKind {
+    fn from(token: &Token) -> SyntaxKind {
+        match token {
+            Token::NumberData(_) => SyntaxKind::Number,
+            Token::Identifier(_) => SyntaxKind::Identifier,
+            Token::Keyword(_) => SyntaxKind::Keyword,
+            Token::Data(_) => SyntaxKind::String,
+            Token::Whitespace(_) => SyntaxKind::Whitespace,
+            Token::Plus(_) => SyntaxKind::Plus,
+            Token::Minus(_) => SyntaxKind::Minus,
+            Token::Multiply(_) => SyntaxKind::Multiply,
+            Token::Divide(_) => SyntaxKind::Divide,
+            Token::Modulo(_) => SyntaxKind::Modulo,
+            Token::LParen(_) => SyntaxKind::LParen,
+            Token::RParen(_) => SyntaxKind::RParen,
+            Token::Dot(_) => SyntaxKind::Dot,
+            Token::Colon(_) => SyntaxKind::Colon,
+            Token::Less(_) => SyntaxKind::Less,
+            Token::Greater(_) => SyntaxKind::Greater,
+            Token::LBracket(_) => SyntaxKind::LBracket,
+            Token::RBracket(_) => SyntaxKind::RBracket,
+            Token::Comma(_) => SyntaxKind::Comma,
+            Token::Question(_) => SyntaxKind::Question,
+            Token::Caret(_) => SyntaxKind::Caret,
+            Token::Ampersand(_) => SyntaxKind::Ampersand,
+            Token::Pipe(_) => SyntaxKind::Pipe,
+            Token::Tilde(_) => SyntaxKind::Tilde,
+            Token::NotEqual(_) => SyntaxKind::NotEqual,
+            Token::EqualEqual(_) => SyntaxKind::EqualEqual,
+            Token::LessEqual(_) => SyntaxKind::LessEqual,
+            Token::GreaterEqual(_) => SyntaxKind::GreaterEqual,
+            Token::Equal(_) => SyntaxKind::Equal,
+            Token::PlusPlus(_) => SyntaxKind::PlusPlus,
+            Token::Newline(_) => SyntaxKind::Newline,
+            Token::Comment(_) => SyntaxKind::Comment,
+            Token::DocComment(_) => SyntaxKind::DocComment,
+            Token::Unknown(_) => SyntaxKind::Unknown,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Trivia {
+    pub kind: SyntaxKind,
+    pub text: String,
+}
+/// a token with the trivia around it
+/// trailing trivia runs to the end of the line, the line break included, leading trivia is everything before that
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GreenToken {
+    kind: SyntaxKind,
+    leading: Vec<Trivia>,
+    text: String,
+    trailing: Vec<Trivia>,
+}
+impl GreenToken {
+    pub fn new(
+        kind: SyntaxKind,
+        leading: Vec<Trivia>,
+        text: String,
+        trailing: Vec<Trivia>,
+    ) -> GreenToken {
+        GreenToken {
+            kind,
+            leading,
+            text,
+            trailing,
+        }
+    }
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+    /// the token itself, no trivia
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    pub fn leading(&self) -> &[Trivia] {
+        &self.leading
+    }
+    pub fn trailing(&self) -> &[Trivia] {
+        &self.trailing
+    }
+    fn leading_width(&self) -> usize {
+        self.leading.iter().map(|trivia| trivia.text.len()).sum()
+    }
+    /// bytes covered, trivia included
+    pub fn width(&self) -> usize {
+        self.leading_width()
+            + self.text.len()
+            + self
+                .trailing
+                .iter()
+                .map(|trivia| trivia.text.len())
+                .sum::<usize>()
+    }
+    fn ends_line(&self) -> bool {
+        self.trailing
+            .last()
+            .is_some_and(|trivia| trivia.kind == SyntaxKind::Newline)
+    }
+}
+impl fmt::Display for GreenToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for trivia in &self.leading {
+            f.write_str(&trivia.text)?;
+        }
+        f.write_str(&self.text)?;
+        for trivia in &self.trailing {
+            f.write_str(&trivia.text)?;
+        }
+        Ok(())
+    }
+}
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GreenElement {
+    Node(Rc<GreenNode>),
+    Token(Rc<GreenToken>),
+}
+impl GreenElement {
+    pub fn width(&self) -> usize {
+        match self {
+            GreenElement::Node(node) => node.width(),
+            GreenElement::Token(token) => token.width(),
+        }
+    }
+    fn ends_line(&self) -> bool {
+        match self {
+            GreenElement::Node(node) => node.children.last().is_some_and(|last| last.ends_line()),
+            GreenElement::Token(token) => token.ends_line(),
+        }
+    }
+}
+impl fmt::Display for GreenElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GreenElement::Node(node) => node.fmt(f),
+            GreenElement::Token(token) => token.fmt(f),
+        }
+    }
+}
+/// knows its kind and width but not where it is, so the same node can sit anywhere
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GreenNode {
+    kind: SyntaxKind,
+    width: usize,
+    children: Vec<GreenElement>,
+}
+impl GreenNode {
+    pub fn new(kind: SyntaxKind, children: Vec<GreenElement>) -> GreenNode {
+        GreenNode {
+            kind,
+            width: children.iter().map(GreenElement::width).sum(),
+            children,
+        }
+    }
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+    pub fn width(&self) -> usize {
+        self.width
+    }
+    pub fn children(&self) -> &[GreenElement] {
+        &self.children
+    }
+}
+impl fmt::Display for GreenNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for child in &self.children {
+            child.fmt(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// a green node at an offset in the source, made on the way down
+#[derive(Clone)]
+pub struct SyntaxNode(Rc<NodeData>);
+struct NodeData {
+    green: Rc<GreenNode>,
+    offset: usize,
+    parent: Option<SyntaxNode>,
+}
+#[derive(Clone)]
+pub struct SyntaxToken {
+    green: Rc<GreenToken>,
+    offset: usize,
+    parent: SyntaxNode,
+}
+#[derive(Clone, Debug)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+impl SyntaxNode {
+    pub fn new_root(green: Rc<GreenNode>) -> SyntaxNode {
+        SyntaxNode(Rc::new(NodeData {
+            green,
+            offset: 0,
+            parent: None,
+        }))
+    }
+    /// lex with trivia and group the tokens into lines and parens, never fails
+    /// the lexer's diagnostics come back along with any unclosed parens
+    pub fn parse(source: &str) -> (SyntaxNode, Diagnostics) {
+        let mut lexer = Lexer::new(source).with_trivia();
+        let tokens: Vec<Spanned<Token>> = lexer.by_ref().collect();
+        let mut diagnostics = lexer.take_diagnostics();
+        let green = Builder {
+            source,
+            tokens: attach_trivia(source, tokens).into_iter().peekable(),
+            diagnostics: &mut diagnostics,
+            end: 0,
+        }
+        .root();
+        (SyntaxNode::new_root(Rc::new(green)), diagnostics)
+    }
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.green.kind
+    }
+    pub fn green(&self) -> &Rc<GreenNode> {
+        &self.0.green
+    }
+    pub fn parent(&self) -> Option<&SyntaxNode> {
+        self.0.parent.as_ref()
+    }
+    /// bytes covered, trivia included
+    pub fn range(&self) -> Range<usize> {
+        self.0.offset..self.0.offset + self.0.green.width
+    }
+    pub fn children(&self) -> Vec<SyntaxElement> {
+        let mut offset = self.0.offset;
+        self.0
+            .green
+            .children
+            .iter()
+            .map(|child| {
+                let start = offset;
+                offset += child.width();
+                match child {
+                    GreenElement::Node(green) => {
+                        SyntaxElement::Node(SyntaxNode(Rc::new(NodeData {
+                            green: green.clone(),
+                            offset: start,
+                            parent: Some(self.clone()),
+                        })))
+                    }
+                    GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+                        green: green.clone(),
+                        offset: start,
+                        parent: self.clone(),
+                    }),
+                }
+            })
+            .collect()
+    }
+    /// every token under this node in source order
+    pub fn tokens(&self) -> Vec<SyntaxToken> {
+        let mut tokens = Vec::new();
+        for child in self.children() {
+            match child {
+                SyntaxElement::Node(node) => tokens.extend(node.tokens()),
+                SyntaxElement::Token(token) => tokens.push(token),
+            }
+        }
+        tokens
+    }
+    /// the token whose text or trivia covers `offset`, for hovers and highlighting
+    pub fn token_at(&self, offset: usize) -> Option<SyntaxToken> {
+        self.tokens()
+            .into_iter()
+            .find(|token| token.full_range().contains(&offset))
+    }
+}
+impl fmt::Display for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.0.green.fmt(f)
+    }
+}
+/// Kind@start..end per line, indented by depth, tokens with their text
+impl fmt::Debug for SyntaxNode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let depth = f.width().unwrap_or(0);
+        writeln!(
+            f,
+            "{:indent$}{:?}@{:?}",
+            "",
+            self.kind(),
+            self.range(),
+            indent = depth
+        )?;
+        for child in self.children() {
+            match child {
+                SyntaxElement::Node(node) => write!(f, "{:depth$?}", node, depth = depth + 2)?,
+                SyntaxElement::Token(token) => writeln!(
+                    f,
+                    "{:indent$}{:?}@{:?} {:?}",
+                    "",
+                    token.kind(),
+                    token.range(),
+                    token.text(),
+                    indent = depth + 2
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+impl SyntaxToken {
+    pub fn kind(&self) -> SyntaxKind {
+        self.green.kind
+    }
+    pub fn text(&self) -> &str {
+        &self.green.text
+    }
+    pub fn green(&self) -> &Rc<GreenToken> {
+        &self.green
+    }
+    pub fn parent(&self) -> &SyntaxNode {
+        &self.parent
+    }
+    /// the token without its trivia
+    pub fn range(&self) -> Range<usize> {
+        let start = self.offset + self.green.leading_width();
+        start..start + self.green.text.len()
+    }
+    pub fn full_range(&self) -> Range<usize> {
+        self.offset..self.offset + self.green.width()
+    }
+    pub fn span(&self, source: &str) -> Span {
+        Span::new(source, self.range())
+    }
+}
+impl fmt::Debug for SyntaxToken {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}@{:?} {:?}", self.kind(), self.range(), self.text())
+    }
+}
+
+/// Roslyn style, a token takes the trivia after it up to and including the next line break
+/// and everything else waits for the next token, ending with an Eof token for what is left
+fn attach_trivia(source: &str, tokens: Vec<Spanned<Token>>) -> Vec<(GreenToken, Span)> {
+    let end = Span::new(source, source.len()..source.len());
+    let mut green = Vec::new();
+    let mut leading = Vec::new();
+    let mut tokens = tokens.into_iter().peekable();
+    let trivia = |token: &Spanned<Token>| Trivia {
+        kind: SyntaxKind::from(&token.node),
+        text: source[token.span.range()].to_string(),
+    };
+    while let Some(token) = tokens.next() {
+        let kind = SyntaxKind::from(&token.node);
+        if kind.is_trivia() {
+            leading.push(trivia(&token));
+            continue;
+        }
+        let mut trailing = Vec::new();
+        while let Some(next) = tokens.next_if(|next| SyntaxKind::from(&next.node).is_trivia()) {
+            trailing.push(trivia(&next));
+            if matches!(next.node, Token::Newline(_)) {
+                break;
+            }
+        }
+        let text = source[token.span.range()].to_string();
+        green.push((
+            GreenToken::new(kind, std::mem::take(&mut leading), text, trailing),
+            token.span,
+        ));
+    }
+    green.push((
+        GreenToken::new(SyntaxKind::Eof, leading, String::new(), Vec::new()),
+        end,
+    ));
+    green
+}
+struct Builder<'a, I: Iterator<Item = (GreenToken, Span)>> {
+    source: &'a str,
+    tokens: std::iter::Peekable<I>,
+    diagnostics: &'a mut Diagnostics,
+    /// end of the last token taken, where a missing `)` goes
+    end: usize,
+}
+impl<I: Iterator<Item = (GreenToken, Span)>> Builder<'_, I> {
+    fn root(mut self) -> GreenNode {
+        let mut children = Vec::new();
+        while self
+            .tokens
+            .peek()
+            .is_some_and(|(token, _)| token.kind != SyntaxKind::Eof)
+        {
+            children.push(GreenElement::Node(Rc::new(self.line())));
+        }
+        children.extend(
+            self.tokens
+                .next()
+                .map(|(eof, _)| GreenElement::Token(Rc::new(eof))),
+        );
+        GreenNode::new(SyntaxKind::Root, children)
+    }
+    fn line(&mut self) -> GreenNode {
+        let mut children = Vec::new();
+        while let Some(element) = self.element() {
+            let ends_line = element.ends_line();
+            children.push(element);
+            if ends_line {
+                break;
+            }
+        }
+        GreenNode::new(SyntaxKind::Line, children)
+    }
+    /// one token, or a whole group if it opens one, None at the end
+    fn element(&mut self) -> Option<GreenElement> {
+        let (token, span) = self
+            .tokens
+            .next_if(|(token, _)| token.kind != SyntaxKind::Eof)?;
+        self.end = span.end;
+        if token.kind != SyntaxKind::LParen {
+            return Some(GreenElement::Token(Rc::new(token)));
+        }
+        let mut children = vec![GreenElement::Token(Rc::new(token))];
+        loop {
+            match self.tokens.peek() {
+                Some((token, _)) if token.kind == SyntaxKind::RParen => {
+                    let (token, close) = self.tokens.next()?;
+                    self.end = close.end;
+                    children.push(GreenElement::Token(Rc::new(token)));
+                    break;
+                }
+                Some((token, _)) if token.kind != SyntaxKind::Eof => {
+                    children.extend(self.element());
+                }
+                _ => {
+                    let end = self.end;
+                    let diagnostic = Diagnostic::error(Code::UnclosedDelimiter, "unclosed `(`")
+                        .label(span, "this paren is never closed")
+                        .suggest(Span::new(self.source, end..end), ")", "close it");
+                    self.diagnostics.push(diagnostic);
+                    break;
+                }
+            }
+        }
+        Some(GreenElement::Node(Rc::new(GreenNode::new(
+            SyntaxKind::Group,
+            children,
+        ))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reproduces_the_input() {
+        let sources = [
+            "",
+            "\n\n",
+            "x = 32 + 5 * 2 - 10 + 10\n\n\n    !@#!$%#^$#&^$(& )\n    \"hello \"\"clarice\"\"\"\n    // 32 + 10\n    // 42",
+            "result = x + - ( y + ( z - x * y + z ) - x * y )\r\n",
+            "  (1 +\n 2 // two\n) * \"open",
+            "é + 1. \\\n 2)",
+        ];
+        for source in sources {
+            let (root, _) = SyntaxNode::parse(source);
+            assert_eq!(root.to_string(), source);
+            assert_eq!(root.range(), 0..source.len());
+            for token in root.tokens() {
+                assert_eq!(&source[token.range()], token.text());
+            }
+        }
+    }
+
+    #[test]
+    fn trivia_sticks_to_tokens() {
+        let source = "// answer\nx  // the x\n\n  + 1";
+        let (root, diagnostics) = SyntaxNode::parse(source);
+        assert!(diagnostics.is_empty());
+        let tokens = root.tokens();
+        let x = tokens[0].green();
+        assert_eq!(x.text(), "x");
+        let text = |trivia: &[Trivia]| -> Vec<String> {
+            trivia.iter().map(|trivia| trivia.text.clone()).collect()
+        };
+        assert_eq!(text(x.leading()), ["// answer", "\n"]);
+        assert_eq!(text(x.trailing()), ["  ", "// the x", "\n"]);
+        assert_eq!(text(tokens[1].green().leading()), ["\n", "  "]);
+        assert_eq!(tokens[1].range(), 25..26);
+        assert_eq!(tokens[1].span(source).to_string(), "4:3");
+        assert_eq!(tokens.last().unwrap().kind(), SyntaxKind::Eof);
+        // hovering a comment finds the token it belongs to
+        assert_eq!(root.token_at(15).unwrap().text(), "x");
+    }
+
+    #[test]
+    fn groups_and_lines() {
+        let (root, diagnostics) = SyntaxNode::parse("a (b\nc) d\ne");
+        assert!(diagnostics.is_empty());
+        let lines: Vec<String> = root
+            .children()
+            .into_iter()
+            .filter_map(|child| match child {
+                SyntaxElement::Node(line) => Some(line.to_string()),
+                SyntaxElement::Token(_) => None,
+            })
+            .collect();
+        assert_eq!(lines, ["a (b\nc) d\n", "e"]);
+        let b = root.token_at(3).unwrap();
+        assert_eq!(b.text(), "b");
+        let group = b.parent();
+        assert_eq!(group.kind(), SyntaxKind::Group);
+        assert_eq!(group.to_string(), "(b\nc) ");
+        assert_eq!(group.parent().unwrap().kind(), SyntaxKind::Line);
+        assert_eq!(
+            format!("{:?}", SyntaxNode::parse("(1)").0),
+            "Root@0..3\n  Line@0..3\n    Group@0..3\n      LParen@0..1 \"(\"\n      Number@1..2 \"1\"\n      RParen@2..3 \")\"\n  Eof@3..3 \"\"\n"
+        );
+    }
+
+    #[test]
+    fn reports_unclosed_groups() {
+        let source = "(1 + (2) // two\n";
+        let (root, diagnostics) = SyntaxNode::parse(source);
+        assert_eq!(root.to_string(), source);
+        assert_eq!(diagnostics.0.len(), 1);
+        assert_eq!(diagnostics.0[0].code, Code::UnclosedDelimiter);
+        assert_eq!(diagnostics.0[0].span(), Some(Span::new(source, 0..1)));
+        // the fix goes before the comment, not inside it
+        assert_eq!(diagnostics.0[0].suggestions[0].span.start, 8);
+    }
+}
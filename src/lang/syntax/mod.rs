@@ -1,3 +1,4 @@
+pub mod cst;
 pub mod trees;
 use crate::lang::repl::token;
 fn main() {
@@ -1,14 +1,34 @@
-use crate::lang::tokens::{RParen, Tokens, Unknown};
+use crate::lang::{
+    span::{Span, Spanned},
+    tokens::{Colon, Keyword, RBracket, RParen, Tokens, Unknown},
+};
 
 use super::super::super::tokens::Token;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Leaf {
     pub token: Token,
+    /// empty for leaves that are not in the source
+    pub span: Span,
+    /// the /// comments above a declaration, on the leaf that names it
+    pub doc: Option<String>,
 }
 impl From<Token> for Leaf {
     fn from(token: Token) -> Leaf {
-        Leaf { token }
+        Leaf {
+            token,
+            span: Span::default(),
+            doc: None,
+        }
+    }
+}
+impl From<Spanned<Token>> for Leaf {
+    fn from(token: Spanned<Token>) -> Leaf {
+        Leaf {
+            token: token.node,
+            span: token.span,
+            doc: None,
+        }
     }
 }
 #[derive(Debug, Clone, PartialEq)]
@@ -23,80 +43,121 @@ impl Leaf {
         Token::Unknown(Unknown).into()
     }
 
+    /// how tightly a prefix operator holds the operand to its right
+    /// not is loose, not a == b is not (a == b)
     pub(crate) fn prefix_stickiness(self) -> Option<u8> {
         match self.token {
-            // Token::NumberData(NumberData) => (13u8),
-            // Token::Identifier(Identifier) => (13u8),
-            // Token::Data(Data) => (13u8),
-            // Token::Whitespace(Whitespace) => (13u8),
-            // Token::Plus(Plus) => Some(13u8),
-            Token::Minus(Minus) => Some(13u8),
-            // Token::Multiply(Multiply) => (13u8),Result<u8, i16>
-            // Token::Divide(Divide) => (13u8),
-            // Token::Modulo(Modulo) => (13u8),
-            // Token::LParen(LParen) => (13u8),
-            // Token::RParen(RParen) => (13u8),
-            // Token::Newline(Newline) => (13u8),
-            // Token::Unknown(Unknown) => (13u8),
-            _ => return None,
+            Token::Minus(_) | Token::Tilde(_) => Some(21),
+            Token::Keyword(Keyword::Not) => Some(8),
+            _ => None,
         }
     }
 
+    /// (left, right) binding power, higher binds tighter
+    /// left < right groups to the left, left > right to the right, (0, 0) ends an expression
+    /// from loosest: = ?: or and | ^ & comparisons + - ++ * / % then . at the top
     pub(crate) fn infix_binding_power(self) -> (u8, u8) {
         match self.token {
-            Token::NumberData(NumberData) => (14, 13),
-            Token::Identifier(Identifier) => (14, 13),
-            Token::Data(Data) => (14, 13),
-            Token::Whitespace(Whitespace) => (14, 13),
-            Token::Plus(Plus) => (14, 13),
-            Token::Minus(Minus) => (14, 13),
-            Token::Multiply(Multiply) => (14, 13),
-            Token::Divide(Divide) => (14, 13),
-            Token::Modulo(Modulo) => (14, 13),
-            Token::LParen(LParen) => (14, 13),
-            Token::RParen(RParen) => (14, 13),
-            Token::Newline(Newline) => (14, 13),
-            Token::Unknown(Unknown) => (14, 13),
+            Token::Equal(_) => (2, 1),
+            Token::Question(_) => (4, 3),
+            Token::Keyword(Keyword::Or) => (5, 6),
+            Token::Keyword(Keyword::And) => (7, 8),
+            Token::Pipe(_) => (9, 10),
+            Token::Caret(_) => (11, 12),
+            Token::Ampersand(_) => (13, 14),
+            Token::EqualEqual(_)
+            | Token::NotEqual(_)
+            | Token::Less(_)
+            | Token::Greater(_)
+            | Token::LessEqual(_)
+            | Token::GreaterEqual(_) => (15, 16),
+            Token::Plus(_) | Token::Minus(_) | Token::PlusPlus(_) => (17, 18),
+            Token::Multiply(_) | Token::Divide(_) | Token::Modulo(_) => (19, 20),
+            Token::Dot(_) => (25, 26),
+            _ => (0, 0),
         }
     }
-    pub(crate) fn postfix_binding_power(self) -> Option<u8> {
+    pub(crate) fn keyword(&self) -> Option<Keyword> {
         match self.token {
-            // Token::NumberData(NumberData) => (14u8),
-            // Token::Identifier(Identifier) => (14u8),
-            // Token::Data(Data) => (14u8),
-            // Token::Whitespace(Whitespace) => (14u8),
-            // Token::Plus(Plus) => (14u8),
-            // Token::Minus(Minus) => (14u8),
-            // Token::Multiply(Multiply) => (14u8),
-            // Token::Divide(Divide) => (14u8),
-            // Token::Modulo(Modulo) => (14u8),
-            // Token::LParen(LParen) => (14u8),
-            Token::RParen(RParen) => Some(14u8),
-            // Token::Newline(Newline) => (14u8),
-            // Token::Unknown(Unknown) => (14u8),
-            _ => return None,
+            Token::Keyword(keyword) => Some(keyword),
+            _ => None,
         }
     }
-
-    pub(crate) fn stickiness(&self) -> ((u8, u8), bool) {
-        let infix_binding_power = &self.clone().infix_binding_power();
-        if let Some(postfix_binding_power) = &self.clone().postfix_binding_power() {
-            if infix_binding_power.1 <= *postfix_binding_power {
-                return ((0, *postfix_binding_power), false);
-            }
+    /// a value on its own
+    pub(crate) fn is_atom(&self) -> bool {
+        matches!(
+            self.token,
+            Token::NumberData(_)
+                | Token::Identifier(_)
+                | Token::Data(_)
+                | Token::Keyword(Keyword::True | Keyword::False)
+        )
+    }
+    pub(crate) fn is_identifier(&self) -> bool {
+        matches!(self.token, Token::Identifier(_))
+    }
+    pub(crate) fn name(&self) -> Option<&str> {
+        match &self.token {
+            Token::Identifier(identifier) => Some(identifier.name()),
+            _ => None,
         }
-        return (*infix_binding_power, true);
     }
-    pub(crate) fn ternary(self) -> bool {
+    pub(crate) fn doc_comment(&self) -> Option<&str> {
+        match &self.token {
+            Token::DocComment(doc) => Some(doc.text()),
+            _ => None,
+        }
+    }
+    pub(crate) fn starts_expression(&self) -> bool {
+        self.is_atom()
+            || self.clone().prefix_stickiness().is_some()
+            || self.closing(false).is_some()
+    }
+    pub(crate) fn starts_statement(&self) -> bool {
+        self.starts_expression()
+            || matches!(
+                self.keyword(),
+                Some(
+                    Keyword::If
+                        | Keyword::While
+                        | Keyword::For
+                        | Keyword::Fn
+                        | Keyword::Print
+                        | Keyword::Return
+                )
+            )
+    }
+    /// comparisons chain, a < b < c is a < b and b < c, not (a < b) < c
+    pub(crate) fn chains(&self) -> bool {
+        matches!(
+            self.token,
+            Token::EqualEqual(_)
+                | Token::NotEqual(_)
+                | Token::Less(_)
+                | Token::Greater(_)
+                | Token::LessEqual(_)
+                | Token::GreaterEqual(_)
+        )
+    }
+    /// calls f(x) and indexing a[i], as tight as . on its left so a.b(c) calls a.b
+    pub(crate) fn postfix_binding_power(self) -> Option<u8> {
         match self.token {
-            _ => false,
+            Token::LParen(_) | Token::LBracket(_) => Some(25),
+            _ => None,
+        }
+    }
+    /// the token between the branches of a ternary, c ? a : b
+    pub(crate) fn ternary(self) -> Option<Token> {
+        match self.token {
+            Token::Question(_) => Token::Colon(Colon).into(),
+            _ => None,
         }
     }
-    pub(crate) fn grouping(self) -> Option<Token> {
+    /// what closes a bracket, `(` groups as a prefix and calls as a postfix, `[` only indexes
+    pub(crate) fn closing(&self, postfix: bool) -> Option<Token> {
         match self.token {
-            Token::LParen(LParen) => Token::RParen(RParen).into(),
-            // prefix( stuff expect)(postfix)
-            // infix? stuff expect:(infix)
+            Token::LParen(_) => Token::RParen(RParen).into(),
+            Token::LBracket(_) if postfix => Token::RBracket(RBracket).into(),
             _ => None,
         }
     }
@@ -1,6 +1,11 @@
 mod leafs;
+mod statements;
 
-use crate::lang::tokens::{Token, Tokens, Unknown};
+use crate::lang::{
+    diagnostics::{Code, Diagnostic, Diagnostics},
+    span::Span,
+    tokens::{Comma, RParen, Token, Tokens},
+};
 
 use self::leafs::{Leaf, Leafs};
 #[derive(Debug, Clone, PartialEq)]
@@ -12,20 +17,20 @@ pub struct Trees(pub(crate) Vec<Tree>);
 // Tree(+, Branch[1, 2])
 // (1 + 2) * (3 + 4)
 /*
-            9(
+             (
 1
-        2+
+        17+18
 2
              ) <--- run 1+2 here (a)
-          5*
-            9(
+          19*20
+             (
 3
-        2+
+        17+18
 4
              ) <--- run 3+4 here (b)
              <--- run a*b here
           */
-//
+// left and right binding power either side of each operator, a group starts again from 0
 //
 // Tree(*, Branch[Tree(+, Branch[1, 2]), Tree(+, Branch[3, 4])])
 
@@ -54,12 +59,64 @@ impl Branch {
     }
 }
 impl Tree {
-    pub(crate) fn new(tokens: Tokens) -> Tree {
-        tokens.into()
+    pub(crate) fn new(source: &str, tokens: Tokens) -> Tree {
+        Tree::parse(source, tokens, &mut Diagnostics::default())
+    }
+    /// new, reporting missing operands and any input the parser stopped before
+    pub(crate) fn parse(source: &str, tokens: Tokens, diagnostics: &mut Diagnostics) -> Tree {
+        let (wood, found) = TreeParse::new(source, Leafs::from(tokens)).parse();
+        diagnostics.extend(found);
+        match wood {
+            Wood::Tree(tree) => tree,
+            Wood::Branch(branch) => branch.into(),
+        }
     }
     pub(crate) fn empty() -> Tree {
         Tree(Leaf::empty(), Branch::Leaf)
     }
+    /// source covered by every leaf in the tree, None if none of them came from the source
+    pub fn span(&self) -> Option<Span> {
+        let spans: Vec<Span> = self
+            .leaves()
+            .iter()
+            .map(|leaf| leaf.span)
+            .filter(|span| !span.is_empty())
+            .collect();
+        let first = spans.iter().min_by_key(|span| span.start)?;
+        Some(spans.iter().fold(*first, |span, other| span.to(*other)))
+    }
+    /// (name, doc) for every documented declaration, in source order
+    pub fn docs(&self) -> Vec<(String, String)> {
+        let mut leaves = self.leaves();
+        leaves.sort_by_key(|leaf| leaf.span.start);
+        leaves
+            .into_iter()
+            .filter_map(|leaf| Some((leaf.name()?.to_string(), leaf.doc.clone()?)))
+            .collect()
+    }
+    /// docs for the name under `offset`, the latest declaration wins
+    pub fn hover(&self, offset: usize) -> Option<String> {
+        let leaves = self.leaves();
+        let name = leaves
+            .iter()
+            .find(|leaf| leaf.span.range().contains(&offset))?
+            .name()?;
+        self.docs()
+            .into_iter()
+            .rev()
+            .find(|(declared, _)| declared == name)
+            .map(|(_, doc)| doc)
+    }
+    fn leaves(&self) -> Vec<&Leaf> {
+        let mut leaves = vec![&self.0];
+        if let Branch::Branch(branch, trees) = &self.1 {
+            leaves.push(branch);
+            for tree in &trees.0 {
+                leaves.extend(tree.leaves());
+            }
+        }
+        leaves
+    }
 }
 impl Trees {
     pub(crate) fn empty() -> Trees {
@@ -71,19 +128,6 @@ impl From<Tree> for Trees {
         Trees(vec![tree])
     }
 }
-impl From<Leafs> for TreeParse {
-    fn from(leafs: Leafs) -> TreeParse {
-        TreeParse::new(leafs)
-    }
-}
-impl From<Tokens> for Tree {
-    fn from(tokens: Tokens) -> Tree {
-        match TreeParse::from(Leafs::from(tokens)).parse() {
-            Wood::Tree(tree) => tree,
-            Wood::Branch(branch) => branch.into(),
-        }
-    }
-}
 impl From<Leaf> for Tree {
     fn from(leaf: Leaf) -> Tree {
         Tree(leaf, Branch::Leaf)
@@ -159,11 +203,12 @@ impl From<Tree> for Branch {
 
 //     stack.0.pop().unwrap_or(Tree::empty()).clone()
 // }
+/// the source is only read to place diagnostics past the last leaf
 #[derive(Debug, Clone, PartialEq)]
-struct TreeParse(usize, Leafs, Tree);
-impl TreeParse {
-    pub(crate) fn new(leafs: Leafs) -> TreeParse {
-        TreeParse(0, leafs, Tree::empty())
+struct TreeParse<'a>(usize, Leafs, Tree, Diagnostics, &'a str);
+impl<'a> TreeParse<'a> {
+    pub(crate) fn new(source: &'a str, leafs: Leafs) -> TreeParse<'a> {
+        TreeParse(0, leafs, Tree::empty(), Diagnostics::default(), source)
     }
     //   # (Left-to-right, leftmost derivation)
     //   # (Left-to-right, Rightmost derivation in reverse)
@@ -186,7 +231,8 @@ impl TreeParse {
     //     # ...
     fn eat(&mut self, val: &Leaf) -> bool {
         if let Some(leaf) = self.peek() {
-            if leaf == val {
+            // same token, wherever it came from
+            if leaf.token == val.token {
                 self.next();
                 true
             } else {
@@ -197,19 +243,6 @@ impl TreeParse {
         }
     }
 
-    fn eat_then_parse_until(mut self, leaf: &Leaf, rbp: u8) -> Branch {
-        Branch::Branch(
-            leaf.clone(),
-            Tree::from(|| -> Branch {
-                if self.eat(leaf) {
-                    return self.parse_until(rbp);
-                } else {
-                    return Branch::empty().into();
-                }
-            }())
-            .into(),
-        )
-    }
     //   fn ParseUntil(&self, rbp):
     //     """
     //     Parse to the right, eating tokens until we encounter a token with binding
@@ -221,41 +254,133 @@ impl TreeParse {
     //     """Initial entry point."""
     //     return self.ParseUntil(0)
     // Purpose of Wood reserved for later
-    fn parse(self) -> Wood {
-        Wood::Branch(self.parse_until(0))
+    // one statement is its own tree, more are the branches of an empty leaf
+    fn parse(mut self) -> (Wood, Diagnostics) {
+        let mut trees = self.statements(&[]);
+        let tree = match trees.len() {
+            0 => Tree::empty(),
+            1 => trees.remove(0),
+            _ => Tree(Leaf::empty(), Branch::Branch(Leaf::empty(), Trees(trees))),
+        };
+        (Wood::Tree(tree), self.3)
     }
     // Parses Tokens Into Nested Tree
     // One can also parse into a single flat tree of trees
     // Write both, from/into the ot_nexther. Can run either directly.
-    fn parse_until(self, minimum_stickiness: u8) -> Branch {
-        match self.clone().peek() {
-            Some(leaf) => {
-                match leaf.clone().prefix_stickiness() {
-                    Some(prefix_stickiness) => {
-                        return self.eat_then_parse_until(leaf, prefix_stickiness);
-                        // if prefix_stickiness > minimum_stickiness {
-                        //     || -> Branch {
-                        //         if let Some(grouping) = leaf.clone().grouping() {
-                        //             while !self.eat(&grouping.into()) {
-                        //                 return self.eat_then_parse_until(leaf, prefix_stickiness);
-                        //             }
-                        //         }
-                        //     }();
-                        // }
-                    }
-                    _ => (),
+    // an operator is Tree(op, Branch[operands]), anything else is Tree(leaf, Leaf)
+    // a comparison chain keeps going in the same node, a < b <= c is Tree(<, Branch[a, b, Tree(<=, Branch[c])])
+    fn parse_until(&mut self, minimum_stickiness: u8) -> Tree {
+        let Some(leaf) = self.peek().cloned().filter(Leaf::starts_expression) else {
+            return Tree::empty();
+        };
+        self.next();
+        // a group is just what is inside, but (a < b) < c must not chain
+        let mut grouped = false;
+        let mut tree = match (leaf.closing(false), leaf.clone().prefix_stickiness()) {
+            (Some(close), _) => {
+                let inner = self.operand(&leaf, 0);
+                self.expect(close, "`)` to close the group");
+                grouped = true;
+                inner
+            }
+            (None, Some(prefix_stickiness)) => {
+                let operand = self.operand(&leaf, prefix_stickiness);
+                Tree(leaf, Branch::Branch(Leaf::empty(), operand.into()))
+            }
+            (None, None) => leaf.into(),
+        };
+        while let Some(leaf) = self.peek().cloned() {
+            // f(x) on one line, but a ( starting the next line is a new statement
+            if let Some(left) = leaf.clone().postfix_binding_power() {
+                if left <= minimum_stickiness || !self.on_same_line(&leaf) {
+                    break;
                 }
-                loop {
-                    let stickiness = leaf.clone().stickiness();
-                    if stickiness.0 .1 > minimum_stickiness {
-                        return self.eat_then_parse_until(leaf, stickiness.0 .1);
-                    } else {
-                        return Branch::from(leaf.clone().clone());
+                self.next();
+                tree = self.postfix(leaf, tree);
+                grouped = false;
+                continue;
+            }
+            let (left, right) = leaf.clone().infix_binding_power();
+            if left <= minimum_stickiness {
+                break;
+            }
+            self.next();
+            if let Some(separator) = leaf.clone().ternary() {
+                let then = self.operand(&leaf, 0);
+                let separator = self.expect(separator, "`:` between the branches");
+                let otherwise = self.operand(&leaf, right);
+                let trees = Trees(vec![tree, then, otherwise]);
+                tree = Tree(leaf, Branch::Branch(separator, trees));
+                grouped = false;
+                continue;
+            }
+            let operand = self.operand(&leaf, right);
+            tree = match tree {
+                Tree(op, Branch::Branch(empty, mut trees))
+                    if !grouped && leaf.chains() && op.chains() && trees.0.len() >= 2 =>
+                {
+                    trees
+                        .0
+                        .push(Tree(leaf, Branch::Branch(Leaf::empty(), operand.into())));
+                    Tree(op, Branch::Branch(empty, trees))
+                }
+                tree => Tree(
+                    leaf,
+                    Branch::Branch(Leaf::empty(), Trees(vec![tree, operand])),
+                ),
+            };
+            grouped = false;
+        }
+        tree
+    }
+    /// callee then arguments for f(a, b), a then the index for a[i], the closing bracket in the branch leaf
+    fn postfix(&mut self, open: Leaf, tree: Tree) -> Tree {
+        let close = open.closing(true).unwrap_or(Token::RParen(RParen));
+        let mut trees = vec![tree];
+        let what = match close {
+            Token::RBracket(_) => {
+                trees.push(self.operand(&open, 0));
+                "`]` after the index"
+            }
+            _ => {
+                while self.peek().is_some_and(|leaf| leaf.token != close) {
+                    trees.push(self.operand(&open, 0));
+                    if !self.eat(&Token::Comma(Comma).into()) {
+                        break;
                     }
                 }
+                "`)` after the arguments"
             }
-            None => Branch::empty(),
+        };
+        let close = self.expect(close, what);
+        Tree(open, Branch::Branch(close, Trees(trees)))
+    }
+    fn on_same_line(&self, leaf: &Leaf) -> bool {
+        self.0
+            .checked_sub(1)
+            .and_then(|last| self.1 .0.get(last))
+            .is_some_and(|last| last.span.line == leaf.span.line)
+    }
+    /// what follows an operator or keyword, reporting it missing
+    fn operand(&mut self, operator: &Leaf, stickiness: u8) -> Tree {
+        let diagnostic = match self.peek() {
+            Some(leaf) if leaf.starts_expression() => None,
+            Some(leaf) => Some((leaf.span, "expected an expression here")),
+            None => Some((self.end_of_input(), "the input ends here")),
+        };
+        if let Some((span, message)) = diagnostic {
+            let diagnostic = Diagnostic::error(Code::ExpectedExpression, "expected an expression")
+                .label(span, message)
+                .secondary(operator.span, "this needs an operand");
+            self.3.push(diagnostic);
         }
+        self.parse_until(stickiness)
+    }
+    /// just past the last leaf
+    fn end_of_input(&self) -> Span {
+        self.1 .0.last().map_or(Span::default(), |last| {
+            Span::new(self.4, last.span.end..last.span.end)
+        })
     }
 }
 
@@ -273,3 +398,219 @@ impl Wood {
         Wood::Tree(tree)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lang::tokens::{tokenize, whole, Multiply, NumberData, Plus};
+
+    /// (op operands..) for operators, source text for the rest, with the branch leaf after a /
+    fn shape(source: &str) -> String {
+        fn walk(source: &str, tree: &Tree) -> String {
+            let leaf = &source[tree.0.span.range()];
+            match &tree.1 {
+                Branch::Branch(end, trees) => {
+                    let mut parts = vec![leaf.to_string()];
+                    parts.extend(trees.0.iter().map(|tree| walk(source, tree)));
+                    if !end.span.is_empty() {
+                        parts.push(format!("/{}", &source[end.span.range()]));
+                    }
+                    format!("({})", parts.join(" "))
+                }
+                Branch::Leaf => leaf.to_string(),
+            }
+        }
+        let (tree, diagnostics) = parse(source);
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        walk(source, &tree)
+    }
+    fn parse(source: &str) -> (Tree, Diagnostics) {
+        let mut diagnostics = Diagnostics::default();
+        let tree = Tree::parse(source, tokenize(source), &mut diagnostics);
+        (tree, diagnostics)
+    }
+    fn codes(source: &str) -> Vec<&'static str> {
+        parse(source)
+            .1
+             .0
+            .iter()
+            .map(|diagnostic| diagnostic.code.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn operators_bind_by_power() {
+        assert_eq!(shape("1 + 2 * 3 - 4"), "(- (+ 1 (* 2 3)) 4)");
+        assert_eq!(shape("x = y = 1 ++ \"s\""), "(= x (= y (++ 1 \"s\")))");
+        assert_eq!(shape("a | b ^ c & d == e"), "(| a (^ b (& c (== d e))))");
+        assert_eq!(shape("-a.b * ~c"), "(* (- (. a b)) (~ c))");
+        assert_eq!(
+            shape("x = a ? b : c ? d : e"),
+            "(= x (? a b (? c d e /:) /:))"
+        );
+        assert_eq!(shape("a or b ? 1 + 2 : 3"), "(? (or a b) (+ 1 2) 3 /:)");
+    }
+
+    #[test]
+    fn matches_the_diagram() {
+        fn unspanned(Tree(leaf, branch): Tree) -> Tree {
+            let branch = match branch {
+                Branch::Branch(leaf, trees) => Branch::Branch(
+                    leaf.token.into(),
+                    Trees(trees.0.into_iter().map(unspanned).collect()),
+                ),
+                Branch::Leaf => Branch::Leaf,
+            };
+            Tree(leaf.token.into(), branch)
+        }
+        let operator = |token: Token, a: u32, b: u32| {
+            let operands =
+                [a, b].map(|n| Leaf::from(Token::from(NumberData::from(whole(n)))).into());
+            Tree(
+                token.into(),
+                Branch::Branch(Leaf::empty(), Trees(operands.to_vec())),
+            )
+        };
+        let expected = Tree(
+            Token::Multiply(Multiply).into(),
+            Branch::Branch(
+                Leaf::empty(),
+                Trees(vec![
+                    operator(Token::Plus(Plus), 1, 2),
+                    operator(Token::Plus(Plus), 3, 4),
+                ]),
+            ),
+        );
+        assert_eq!(
+            unspanned(Tree::new(
+                "(1 + 2) * (3 + 4)",
+                tokenize("(1 + 2) * (3 + 4)")
+            )),
+            expected
+        );
+        assert_eq!(shape("1 + 2 * 3"), "(+ 1 (* 2 3))");
+    }
+
+    #[test]
+    fn calls_and_indexing() {
+        assert_eq!(shape("add(40, 2)"), "(( add 40 2 /))");
+        assert_eq!(shape("f()"), "(( f /))");
+        assert_eq!(
+            shape("-a.b(c)[0] * 2"),
+            "(* (- ([ (( (. a b) c /)) 0 /])) 2)"
+        );
+        assert_eq!(
+            shape("print f(g(x) + 1)"),
+            "(print (( f (+ (( g x /)) 1) /)))"
+        );
+        // a bracket on the next line starts a new statement
+        assert_eq!(shape("x\n(1)"), "( x 1)");
+        assert_eq!(codes("f(1, 2"), ["E0103"]);
+        assert_eq!(codes("a ? b"), ["E0103", "E0100"]);
+    }
+
+    #[test]
+    fn comparisons_chain() {
+        assert_eq!(shape("a < b < c"), "(< a b (< c))");
+        assert_eq!(
+            shape("0 <= x + 1 < 10 != y"),
+            "(<= 0 (+ x 1) (< 10) (!= y))"
+        );
+        assert_eq!(shape("a < b = c > d"), "(= (< a b) (> c d))");
+    }
+
+    #[test]
+    fn negative_literals() {
+        assert_eq!(shape("x = --0"), "(= x (- -0))");
+        assert_eq!(shape("x - -1"), "(- x -1)");
+        assert_eq!(shape("-(1)"), "(- 1)");
+        assert_eq!(shape("1-1"), "(- 1 1)");
+        assert_eq!(shape("-1 * 2"), "(* -1 2)");
+    }
+
+    #[test]
+    fn groups_and_interpolation() {
+        assert_eq!(shape("(1 + 2) * 3"), "(* (+ 1 2) 3)");
+        assert_eq!(shape("(a < b) < c"), "(< (< a b) c)");
+        // the ++ leaves sit on the braces
+        assert_eq!(shape("\"a{x + 1}b\""), "(} ({ \"a (+ x 1)) b\")");
+        assert_eq!(codes("(1 + 2"), ["E0103"]);
+    }
+
+    #[test]
+    fn reports_missing_operands() {
+        assert_eq!(codes("1 <"), ["E0100"]);
+        assert_eq!(codes("print"), ["E0100"]);
+        assert_eq!(codes("1 + then"), ["E0100", "E0101"]);
+    }
+
+    #[test]
+    fn input_ends_past_the_last_token() {
+        fn end(source: &str) -> (usize, usize) {
+            let span = parse(source).1 .0[0].labels[0].span;
+            (span.line, span.col)
+        }
+        assert_eq!(end("1 +"), (1, 4));
+        assert_eq!(end("if \"é\""), (1, 7));
+        assert_eq!(end("if \"\"\"é\nab\"\"\""), (2, 6));
+    }
+
+    #[test]
+    fn keywords_make_statements() {
+        assert_eq!(
+            shape("if result then print result else print 1 end"),
+            "(if result (then (print result)) (else (print 1)) /end)"
+        );
+        assert_eq!(
+            shape("if x == 1 then\n  x = 2\nend if\nprint x"),
+            "( (if (== x 1) (then (= x 2)) /end) (print x))"
+        );
+        assert_eq!(
+            shape("if a then 1 else if b then 2 else if c then 3 else 4 end"),
+            "(if a (then 1) (else (if b (then 2) (else (if c (then 3) (else 4))))) /end)"
+        );
+        assert_eq!(
+            shape("while not done and x < 10 x = x + 1 end"),
+            "(while (and (not done) (< x 10)) ( (= x (+ x 1))) /end)"
+        );
+        assert_eq!(
+            shape("for i = 1, 10 print i end"),
+            "(for (= i 1) 10 ( (print i)) /end)"
+        );
+        assert_eq!(
+            shape("fn add(a, b) return a + b end fn\nfn nothing() return end"),
+            "( (fn add (( a b /)) ( (return (+ a b))) /end) (fn nothing (( /)) ( return) /end))"
+        );
+        assert_eq!(shape("x = true or false"), "(= x (or true false))");
+    }
+
+    #[test]
+    fn docs_attach_to_declarations() {
+        let source = "/// adds\n/// two numbers\nfn add(a, b) return a + b end\n// not a doc\n/// the answer\nx = 40 + 2\nprint add\n";
+        let (tree, diagnostics) = parse(source);
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+        assert_eq!(
+            tree.docs(),
+            [
+                ("add".to_string(), "adds\ntwo numbers".to_string()),
+                ("x".to_string(), "the answer".to_string())
+            ]
+        );
+        let usage = source.rfind("add").unwrap();
+        assert_eq!(tree.hover(usage).as_deref(), Some("adds\ntwo numbers"));
+        assert_eq!(tree.hover(source.find("a + b").unwrap()), None);
+        assert_eq!(codes("/// lost\nprint 1"), ["W0001"]);
+        assert_eq!(codes("if x then /// lost\nend"), ["W0001"]);
+    }
+
+    #[test]
+    fn reports_broken_statements() {
+        assert_eq!(codes("if x print x end"), ["E0103"]);
+        assert_eq!(codes("while x print x"), ["E0104"]);
+        assert_eq!(codes("for i = 1 10 end"), ["E0103"]);
+        assert_eq!(codes("end"), ["E0101"]);
+        // the sample from the tokens test parses clean apart from its triple quoted string
+        let sample = "x = 10\n    y = 20\n    z = 30\n\n    result = x + - ( y + ( z - x * y + z ) - x * y )\n\n    if result then\n        print result\n    else\n        print 1\n    end\n    ";
+        assert!(parse(sample).0.span().is_some());
+    }
+}
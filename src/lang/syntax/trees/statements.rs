@@ -0,0 +1,225 @@
+// statements, each a Tree with its keyword as the leaf and its parts as the branch
+// blocks put their `end` in the branch leaf, empty when it is missing
+//   if c then a else if d then b else e end     Tree(if, Branch(end, [c, (then [a]), (else [(if [d, (then [b]), (else [e])])])]))
+//   while c ... end                             Tree(while, Branch(end, [c, ([...])]))
+//   for i = 1, 10 ... end                       Tree(for, Branch(end, [(= i 1), 10, ([...])]))
+//   fn name(a, b) ... end                       Tree(fn, Branch(end, [name, ((, Branch(), [a, b])), ([...])]))
+//   print x, return x                           Tree(print, Branch[x]), return alone is just the leaf
+// `else if` always continues the chain, so it shares the one `end`
+use super::{Branch, Leaf, Tree, TreeParse, Trees};
+use crate::lang::{
+    diagnostics::{Code, Diagnostic},
+    span::Span,
+    tokens::{Comma, Keyword, LParen, RParen, Token},
+};
+
+impl TreeParse<'_> {
+    /// statements up to one of `until` or the end, whatever cannot start one is reported and skipped
+    /// /// comments go to the declaration right after them
+    pub(super) fn statements(&mut self, until: &[Keyword]) -> Vec<Tree> {
+        let mut trees = Vec::new();
+        let mut doc: Option<(String, Span)> = None;
+        while let Some(leaf) = self.peek().cloned() {
+            if let Some(text) = leaf.doc_comment() {
+                match &mut doc {
+                    Some((docs, span)) => {
+                        docs.push('\n');
+                        docs.push_str(text);
+                        *span = span.to(leaf.span);
+                    }
+                    None => doc = Some((text.to_string(), leaf.span)),
+                }
+                self.next();
+                continue;
+            }
+            if leaf
+                .keyword()
+                .is_some_and(|keyword| until.contains(&keyword))
+            {
+                break;
+            }
+            if !leaf.starts_statement() {
+                let diagnostic = Diagnostic::error(Code::UnexpectedToken, "unexpected token")
+                    .label(leaf.span, "a statement cannot start here, skipped");
+                self.3.push(diagnostic);
+                self.next();
+                continue;
+            }
+            let mut tree = self.statement();
+            if let Some((text, span)) = doc.take() {
+                match declared(&mut tree) {
+                    Some(name) => name.doc = Some(text),
+                    None => self.dangling(span),
+                }
+            }
+            trees.push(tree);
+        }
+        if let Some((_, span)) = doc {
+            self.dangling(span);
+        }
+        trees
+    }
+    fn dangling(&mut self, span: Span) {
+        let diagnostic =
+            Diagnostic::warning(Code::DanglingDocComment, "doc comment documents nothing")
+                .label(span, "only `fn` and assignments can have docs")
+                .note("use `//` for a plain comment");
+        self.3.push(diagnostic);
+    }
+    fn statement(&mut self) -> Tree {
+        let Some(leaf) = self.peek().cloned() else {
+            return Tree::empty();
+        };
+        let keyword = match leaf.keyword() {
+            Some(
+                keyword @ (Keyword::If
+                | Keyword::While
+                | Keyword::For
+                | Keyword::Fn
+                | Keyword::Print
+                | Keyword::Return),
+            ) => keyword,
+            _ => return self.parse_until(0),
+        };
+        self.next();
+        match keyword {
+            Keyword::If => {
+                let trees = self.conditional(&leaf);
+                self.close(leaf, trees)
+            }
+            Keyword::While => {
+                let condition = self.operand(&leaf, 0);
+                let body = self.block(Leaf::empty(), &[Keyword::End]);
+                self.close(leaf, vec![condition, body])
+            }
+            Keyword::For => {
+                let start = self.operand(&leaf, 0);
+                self.expect(Token::Comma(Comma), "`,` before the limit");
+                let limit = self.operand(&leaf, 0);
+                let body = self.block(Leaf::empty(), &[Keyword::End]);
+                self.close(leaf, vec![start, limit, body])
+            }
+            Keyword::Fn => {
+                let name = self.name();
+                let open = self.expect(Token::LParen(LParen), "`(` before the parameters");
+                let mut parameters = Vec::new();
+                while self.peek().is_some_and(|leaf| leaf.is_identifier()) {
+                    parameters.push(self.name());
+                    if !self.eat(&Token::Comma(Comma).into()) {
+                        break;
+                    }
+                }
+                let close = self.expect(Token::RParen(RParen), "`)` after the parameters");
+                let parameters = Tree(open, Branch::Branch(close, Trees(parameters)));
+                let body = self.block(Leaf::empty(), &[Keyword::End]);
+                self.close(leaf, vec![name, parameters, body])
+            }
+            Keyword::Print => {
+                let value = self.operand(&leaf, 0);
+                Tree(leaf, Branch::Branch(Leaf::empty(), value.into()))
+            }
+            _ if self.peek().is_some_and(Leaf::starts_expression) => {
+                let value = self.parse_until(0);
+                Tree(leaf, Branch::Branch(Leaf::empty(), value.into()))
+            }
+            _ => leaf.into(),
+        }
+    }
+    /// condition, then block and maybe an else, without the `end`
+    fn conditional(&mut self, leaf: &Leaf) -> Vec<Tree> {
+        let condition = self.operand(leaf, 0);
+        let then = self.expect(Token::Keyword(Keyword::Then), "`then` after the condition");
+        let body = self.block(then, &[Keyword::Else, Keyword::End]);
+        let mut trees = vec![condition, body];
+        if let Some(otherwise) = self.eat_keyword(Keyword::Else) {
+            let body = match self.eat_keyword(Keyword::If) {
+                Some(leaf) => {
+                    let chain = Trees(self.conditional(&leaf));
+                    vec![Tree(leaf, Branch::Branch(Leaf::empty(), chain))]
+                }
+                None => self.statements(&[Keyword::End]),
+            };
+            trees.push(Tree(otherwise, Branch::Branch(Leaf::empty(), Trees(body))));
+        }
+        trees
+    }
+    fn block(&mut self, leaf: Leaf, until: &[Keyword]) -> Tree {
+        let body = self.statements(until);
+        Tree(leaf, Branch::Branch(Leaf::empty(), Trees(body)))
+    }
+    /// `end`, or `end if` spelling out what it closes when that is on the same line
+    fn close(&mut self, opener: Leaf, trees: Vec<Tree>) -> Tree {
+        let end = match self.eat_keyword(Keyword::End) {
+            Some(end) => {
+                if self.peek().is_some_and(|leaf| {
+                    leaf.keyword() == opener.keyword() && leaf.span.line == end.span.line
+                }) {
+                    self.next();
+                }
+                end
+            }
+            None => {
+                let keyword = opener.keyword().map_or("", |keyword| keyword.as_str());
+                let diagnostic = Diagnostic::error(
+                    Code::UnclosedBlock,
+                    format!("`{}` without a matching `end`", keyword),
+                )
+                .label(opener.span, "this block is never closed")
+                .secondary(self.here(), "expected `end` here");
+                self.3.push(diagnostic);
+                Leaf::empty()
+            }
+        };
+        Tree(opener, Branch::Branch(end, Trees(trees)))
+    }
+    fn name(&mut self) -> Tree {
+        match self.peek().cloned().filter(Leaf::is_identifier) {
+            Some(leaf) => {
+                self.next();
+                leaf.into()
+            }
+            None => {
+                self.expected("a name");
+                Tree::empty()
+            }
+        }
+    }
+    fn eat_keyword(&mut self, keyword: Keyword) -> Option<Leaf> {
+        let leaf = self.peek().cloned()?;
+        self.eat(&Token::Keyword(keyword).into()).then_some(leaf)
+    }
+    /// the next leaf if it is `token`, otherwise an empty one and a complaint
+    pub(super) fn expect(&mut self, token: Token, what: &str) -> Leaf {
+        if let Some(leaf) = self.peek().cloned().filter(|leaf| leaf.token == token) {
+            self.next();
+            return leaf;
+        }
+        self.expected(what);
+        Leaf::empty()
+    }
+    fn expected(&mut self, what: &str) {
+        let found = match self.peek() {
+            Some(_) => "found this instead",
+            None => "the input ends here",
+        };
+        let diagnostic = Diagnostic::error(Code::ExpectedToken, format!("expected {}", what))
+            .label(self.here(), found);
+        self.3.push(diagnostic);
+    }
+    /// the next leaf or the end of the input
+    fn here(&self) -> Span {
+        self.peek().map_or(self.end_of_input(), |leaf| leaf.span)
+    }
+}
+
+/// the name a statement declares, `fn name` or `name = value`
+fn declared(tree: &mut Tree) -> Option<&mut Leaf> {
+    let Tree(leaf, Branch::Branch(_, trees)) = tree else {
+        return None;
+    };
+    let declares = leaf.keyword() == Some(Keyword::Fn) || matches!(leaf.token, Token::Equal(_));
+    match trees.0.first_mut() {
+        Some(Tree(name, Branch::Leaf)) if declares && name.is_identifier() => Some(name),
+        _ => None,
+    }
+}
@@ -0,0 +1,1199 @@
+// streaming lexer, one token per call to next
+// with trivia kept the spans of everything it yields cover the source exactly, nothing dropped
+// in partial mode (a repl line) it stops at an unfinished string or a trailing `\` and can resume
+// once the next line is appended
+use std::collections::VecDeque;
+
+use super::*;
+use crate::lang::{
+    diagnostics::{Code, Diagnostic, Diagnostics},
+    span::{Span, Spanned},
+};
+
+/// a position in the source, where a resumed lexer starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint {
+    offset: usize,
+    line: usize,
+    col: usize,
+    /// the last token could end an operand, so a `-` after it subtracts rather than signs a number
+    after_operand: bool,
+}
+impl Default for Checkpoint {
+    fn default() -> Self {
+        Checkpoint {
+            offset: 0,
+            line: 1,
+            col: 1,
+            after_operand: false,
+        }
+    }
+}
+impl Checkpoint {
+    fn advance(&mut self, c: char) {
+        self.offset += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+    }
+    fn to(&self, end: Checkpoint) -> Span {
+        Span {
+            start: self.offset,
+            end: end.offset,
+            line: self.line,
+            col: self.col,
+        }
+    }
+}
+pub struct Lexer<'a> {
+    source: &'a str,
+    cursor: Checkpoint,
+    trivia: bool,
+    partial: bool,
+    /// bits a number may take
+    width: u32,
+    lookahead: VecDeque<Spanned<Token>>,
+    /// the rest of a string that came out as several tokens
+    pending: VecDeque<Spanned<Token>>,
+    diagnostics: Diagnostics,
+    incomplete: Option<Checkpoint>,
+}
+impl<'a> Lexer<'a> {
+    pub fn new(source: &'a str) -> Lexer<'a> {
+        Lexer::resume(source, Checkpoint::default())
+    }
+    /// carry on from `checkpoint` in a source that starts with the one lexed before
+    pub fn resume(source: &'a str, checkpoint: Checkpoint) -> Lexer<'a> {
+        Lexer {
+            source,
+            cursor: checkpoint,
+            trivia: false,
+            partial: false,
+            width: 16,
+            lookahead: VecDeque::new(),
+            pending: VecDeque::new(),
+            diagnostics: Diagnostics::default(),
+            incomplete: None,
+        }
+    }
+    /// also yield whitespace, newlines, continuations and unknown text
+    pub fn with_trivia(mut self) -> Lexer<'a> {
+        self.trivia = true;
+        self
+    }
+    /// more input may follow, so an unfinished string or a trailing `\` waits for it instead of being an error
+    pub fn partial(mut self) -> Lexer<'a> {
+        self.partial = true;
+        self
+    }
+    /// bits a number literal may take, 16 by default for the widest chip-8 register
+    pub fn width(mut self, bits: u32) -> Lexer<'a> {
+        self.width = bits;
+        self
+    }
+    #[cfg(test)]
+    pub fn peek(&mut self) -> Option<&Spanned<Token>> {
+        self.peek_nth(0)
+    }
+    /// the token `n` places after the next one, without consuming anything
+    #[cfg(test)]
+    pub fn peek_nth(&mut self, n: usize) -> Option<&Spanned<Token>> {
+        while self.lookahead.len() <= n {
+            let token = self.significant()?;
+            self.lookahead.push_back(token);
+        }
+        self.lookahead.get(n)
+    }
+    #[cfg(test)]
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+    pub fn take_diagnostics(&mut self) -> Diagnostics {
+        std::mem::take(&mut self.diagnostics)
+    }
+    /// how far it has read
+    pub fn checkpoint(&self) -> Checkpoint {
+        self.cursor
+    }
+    /// where to resume once more input arrives, only in partial mode and only if the input stopped mid token
+    pub fn incomplete(&self) -> Option<Checkpoint> {
+        self.incomplete
+    }
+    fn significant(&mut self) -> Option<Spanned<Token>> {
+        loop {
+            let token = self.lex()?;
+            if !is_trivia(&token.node) {
+                self.cursor.after_operand = ends_operand(&token.node);
+            } else if !self.trivia {
+                continue;
+            }
+            return Some(token);
+        }
+    }
+    fn rest(&self) -> &'a str {
+        &self.source[self.cursor.offset..]
+    }
+    fn first(&self) -> Option<char> {
+        self.rest().chars().next()
+    }
+    fn second(&self) -> Option<char> {
+        self.rest().chars().nth(1)
+    }
+    fn bump(&mut self) -> Option<char> {
+        let c = self.first()?;
+        self.cursor.advance(c);
+        Some(c)
+    }
+    /// r" or r#", the start of a raw string rather than a name
+    fn raw_string_ahead(&self) -> bool {
+        let hashes = self.rest()[1..].trim_start_matches('#');
+        hashes.starts_with('"')
+    }
+    fn eat_while(&mut self, f: impl Fn(char) -> bool) {
+        while self.first().is_some_and(&f) {
+            self.bump();
+        }
+    }
+    /// one line break, \r\n counts once
+    fn eat_newline(&mut self) {
+        if self.bump() == Some('\r') && self.first() == Some('\n') {
+            self.bump();
+        }
+    }
+    fn lex(&mut self) -> Option<Spanned<Token>> {
+        if let Some(token) = self.pending.pop_front() {
+            return Some(token);
+        }
+        if self.incomplete.is_some() {
+            return None;
+        }
+        let start = self.cursor;
+        let token = match self.first()? {
+            '\n' | '\r' => {
+                self.eat_newline();
+                Token::Newline(Newline)
+            }
+            '\\' if matches!(self.second(), Some('\n' | '\r')) => {
+                // the line goes on, so the break is just whitespace
+                self.bump();
+                self.eat_newline();
+                if self.partial && self.first().is_none() {
+                    self.incomplete = Some(self.cursor);
+                }
+                Token::Whitespace(Whitespace())
+            }
+            '\\' if self.partial && self.second().is_none() => {
+                self.incomplete = Some(start);
+                return None;
+            }
+            c if is_space(c) => {
+                self.eat_while(is_space);
+                Token::Whitespace(Whitespace())
+            }
+            '/' if self.second() == Some('/') => {
+                self.eat_while(|c| !matches!(c, '\n' | '\r'));
+                let text = &self.source[start.offset + 2..self.cursor.offset];
+                // exactly three slashes, //// is back to a plain comment
+                match text.strip_prefix('/') {
+                    Some(doc) if !doc.starts_with('/') => {
+                        Token::DocComment(DocComment(doc.to_string()))
+                    }
+                    _ => Token::Comment(Comment(text.to_string())),
+                }
+            }
+            '/' if self.second() == Some('*') => self.block_comment(start)?,
+            '"' => return self.string(start),
+            'r' if self.raw_string_ahead() => self.raw_string(start)?,
+            '-' if !self.cursor.after_operand
+                && self.second().is_some_and(|c| c.is_ascii_digit()) =>
+            {
+                self.bump();
+                self.number(start, true)
+            }
+            c if c.is_ascii_digit() => self.number(start, false),
+            c if is_identifier_start(c) => {
+                self.eat_while(is_identifier);
+                let name = &self.source[start.offset..self.cursor.offset];
+                match Keyword::lookup(name) {
+                    Some(keyword) => Token::Keyword(keyword),
+                    None => Token::Identifier(Identifier(name.to_string())),
+                }
+            }
+            _ => match operator(self.rest()) {
+                Some((token, len)) => {
+                    for _ in 0..len {
+                        self.bump();
+                    }
+                    token
+                }
+                None => self.unknown(start),
+            },
+        };
+        Some(Spanned::new(token, start.to(self.cursor)))
+    }
+    /// /* text */, nesting so a commented out block can hold comments of its own
+    fn block_comment(&mut self, start: Checkpoint) -> Option<Token> {
+        self.bump();
+        self.bump();
+        let mut depth = 1;
+        let end = loop {
+            match (self.first(), self.second()) {
+                (Some('/'), Some('*')) => depth += 1,
+                (Some('*'), Some('/')) if depth == 1 => {
+                    let end = self.cursor.offset;
+                    self.bump();
+                    self.bump();
+                    break end;
+                }
+                (Some('*'), Some('/')) => depth -= 1,
+                (Some(_), _) => {
+                    self.bump();
+                    continue;
+                }
+                (None, _) if self.partial => {
+                    self.incomplete = Some(start);
+                    return None;
+                }
+                (None, _) => {
+                    let close = self.source.trim_end_matches(['\n', '\r']).len();
+                    let diagnostic =
+                        Diagnostic::error(Code::UnterminatedComment, "unterminated block comment")
+                            .label(
+                                start.to(Checkpoint {
+                                    offset: start.offset + 2,
+                                    ..start
+                                }),
+                                "this comment is never closed",
+                            )
+                            .note("block comments nest, every `/*` needs its own `*/`")
+                            .suggest(Span::new(self.source, close..close), "*/", "close it");
+                    self.diagnostics.push(diagnostic);
+                    break self.cursor.offset;
+                }
+            }
+            self.bump();
+            self.bump();
+        };
+        Some(Token::Comment(Comment(
+            self.source[start.offset + 2..end].to_string(),
+        )))
+    }
+    /// "text" or """text""", with escapes and {expression} interpolation
+    /// a doubled "" is still a quote in a "text" string
+    /// interpolation comes out as concatenation, "a{x}b" is "a" ++ (x) ++ "b", except with trivia
+    /// where the whole literal stays one token so the spans still cover the source exactly
+    fn string(&mut self, start: Checkpoint) -> Option<Spanned<Token>> {
+        let quotes = if self.rest().starts_with("\"\"\"") {
+            3
+        } else {
+            1
+        };
+        for _ in 0..quotes {
+            self.bump();
+        }
+        let mut tokens = Vec::new();
+        let mut text = String::new();
+        let mut piece = start;
+        loop {
+            match self.first() {
+                Some('"') if quotes == 3 && self.rest().starts_with("\"\"\"") => {
+                    self.eat_chars(3);
+                    break;
+                }
+                Some('"') if quotes == 1 && self.second() == Some('"') => {
+                    self.bump();
+                    self.bump();
+                    text.push('"');
+                }
+                Some('"') if quotes == 1 => {
+                    self.bump();
+                    break;
+                }
+                Some('\\') => self.escape(&mut text),
+                Some(c @ ('{' | '}')) if self.second() == Some(c) => {
+                    self.bump();
+                    self.bump();
+                    text.push(c);
+                }
+                Some('{') if !self.trivia => {
+                    let brace = self.cursor;
+                    let Some(inner) = self.interpolation() else {
+                        text.push('{');
+                        continue;
+                    };
+                    let open = brace.to(Checkpoint {
+                        offset: brace.offset + 1,
+                        ..brace
+                    });
+                    tokens.push(Spanned::new(
+                        Token::Data(Data(std::mem::take(&mut text))),
+                        piece.to(brace),
+                    ));
+                    tokens.push(Spanned::new(Token::PlusPlus(PlusPlus), open));
+                    tokens.push(Spanned::new(Token::LParen(LParen), open));
+                    tokens.extend(inner);
+                    let close = self.cursor;
+                    self.bump();
+                    tokens.push(Spanned::new(Token::RParen(RParen), close.to(self.cursor)));
+                    tokens.push(Spanned::new(
+                        Token::PlusPlus(PlusPlus),
+                        close.to(self.cursor),
+                    ));
+                    piece = self.cursor;
+                }
+                Some(c) => {
+                    self.bump();
+                    text.push(c);
+                }
+                None if self.partial => {
+                    self.incomplete = Some(start);
+                    return None;
+                }
+                None => {
+                    self.unterminated(start, &"\"".repeat(quotes));
+                    break;
+                }
+            }
+        }
+        tokens.push(Spanned::new(Token::Data(Data(text)), piece.to(self.cursor)));
+        let first = tokens.remove(0);
+        self.pending.extend(tokens);
+        Some(first)
+    }
+    /// r"text" or r#"text with "quotes""#, nothing is escaped
+    fn raw_string(&mut self, start: Checkpoint) -> Option<Token> {
+        self.bump();
+        let hashes = self.rest().chars().take_while(|c| *c == '#').count();
+        for _ in 0..=hashes {
+            self.bump();
+        }
+        let close = format!("\"{}", "#".repeat(hashes));
+        match self.rest().find(&close) {
+            Some(len) => {
+                let text = self.rest()[..len].to_string();
+                self.eat_chars(len + close.len());
+                Some(Token::Data(Data(text)))
+            }
+            None if self.partial => {
+                self.incomplete = Some(start);
+                None
+            }
+            None => {
+                let text = self.rest().to_string();
+                self.eat_chars(text.len());
+                self.unterminated(start, &close);
+                Some(Token::Data(Data(text)))
+            }
+        }
+    }
+    /// move on by `len` bytes
+    fn eat_chars(&mut self, len: usize) {
+        let end = self.cursor.offset + len;
+        while self.cursor.offset < end {
+            self.bump();
+        }
+    }
+    fn unterminated(&mut self, start: Checkpoint, close: &str) {
+        // close it before the final line break, where it was most likely meant to end
+        let end = self.source.trim_end_matches(['\n', '\r']).len();
+        let end = end.max(start.offset + 1);
+        let diagnostic = Diagnostic::error(Code::UnterminatedString, "unterminated string")
+            .label(
+                start.to(Checkpoint {
+                    offset: start.offset + 1,
+                    ..start
+                }),
+                "this string is never closed",
+            )
+            .note(format!("this string ends with `{}`", close))
+            .suggest(Span::new(self.source, end..end), close, "close it");
+        self.diagnostics.push(diagnostic);
+    }
+    /// \n \t \" \\ or \u{hex}, anything else is reported and kept as written
+    fn escape(&mut self, text: &mut String) {
+        let start = self.cursor;
+        self.bump();
+        let escaped = match self.bump() {
+            Some('n') => Some('\n'),
+            Some('t') => Some('\t'),
+            Some('"') => Some('"'),
+            Some('\\') => Some('\\'),
+            Some('u') if self.first() == Some('{') => {
+                self.bump();
+                let digits = self
+                    .rest()
+                    .chars()
+                    .take_while(|c| *c != '}' && *c != '"')
+                    .count();
+                let hex = &self.rest()[..digits];
+                let code = u32::from_str_radix(hex, 16).ok().filter(|_| hex.len() <= 6);
+                self.eat_chars(digits);
+                if self.first() == Some('}') {
+                    self.bump();
+                    code.and_then(char::from_u32)
+                } else {
+                    None
+                }
+            }
+            // the string loop reports the missing end
+            None => return,
+            Some(_) => None,
+        };
+        match escaped {
+            Some(c) => text.push(c),
+            None => {
+                let written = &self.source[start.offset..self.cursor.offset];
+                let diagnostic = Diagnostic::error(
+                    Code::InvalidEscape,
+                    format!("invalid escape `{}`", written),
+                )
+                .label(start.to(self.cursor), "not a known escape")
+                .note("strings know \\n \\t \\\" \\\\ and \\u{1F600}, a raw string r\"..\" escapes nothing");
+                self.diagnostics.push(diagnostic);
+                text.push_str(written);
+            }
+        }
+    }
+    /// the tokens of {expression}, leaving the cursor on the `}`
+    /// None when there is no `}` before the string or the line ends
+    fn interpolation(&mut self) -> Option<Vec<Spanned<Token>>> {
+        let brace = self.cursor;
+        let len = self.rest().find(['}', '"', '\n', '\r'])?;
+        if !self.rest()[len..].starts_with('}') {
+            let diagnostic = Diagnostic::error(Code::UnclosedDelimiter, "unclosed `{` in string")
+                .label(
+                    brace.to(Checkpoint {
+                        offset: brace.offset + 1,
+                        ..brace
+                    }),
+                    "this interpolation is never closed",
+                )
+                .note("write `{{` for a brace in the text");
+            self.diagnostics.push(diagnostic);
+            self.bump();
+            return None;
+        }
+        self.bump();
+        // an interpolation starts a fresh expression, whatever came before the string
+        let start = Checkpoint {
+            after_operand: false,
+            ..self.cursor
+        };
+        let mut inner = Lexer::resume(&self.source[..brace.offset + len], start).width(self.width);
+        let tokens: Vec<Spanned<Token>> = inner.by_ref().collect();
+        self.diagnostics.extend(inner.take_diagnostics());
+        if tokens.is_empty() {
+            let diagnostic = Diagnostic::error(Code::ExpectedExpression, "empty interpolation")
+                .label(
+                    brace.to(inner.cursor),
+                    "expected an expression between the braces",
+                );
+            self.diagnostics.push(diagnostic);
+        }
+        self.cursor = inner.cursor;
+        Some(tokens)
+    }
+    /// 0x2A, 0b1010_0101, 1_000 or 1.25, kept exact as a reduced fraction
+    /// `_` only separates digits, a fraction is decimal only
+    /// `negative` when a `-` in prefix position came right before, -1 is one literal while x-1 and - 1 subtract
+    fn number(&mut self, start: Checkpoint, negative: bool) -> Token {
+        let (radix, base) = match (self.first(), self.second()) {
+            (Some('0'), Some('x' | 'X')) => (16, "hex"),
+            (Some('0'), Some('b' | 'B')) => (2, "binary"),
+            _ => (10, "decimal"),
+        };
+        let mut value = whole(0);
+        if radix != 10 {
+            self.eat_chars(2);
+            if self.digits(radix, base, &mut value) == 0 {
+                let diagnostic = Diagnostic::error(
+                    Code::InvalidNumber,
+                    format!(
+                        "`{}` without digits",
+                        &self.source[start.offset..self.cursor.offset]
+                    ),
+                )
+                .label(start.to(self.cursor), format!("expected {} digits", base))
+                .note("zero is `0`");
+                self.diagnostics.push(diagnostic);
+            }
+        } else {
+            self.digits(10, base, &mut value);
+            if self.first() == Some('.') && self.second().is_some_and(|c| c.is_ascii_digit()) {
+                self.bump();
+                let mut fraction = whole(0);
+                let places = self.digits(10, base, &mut fraction);
+                let scale = (0..places).fold(whole(1), |scale, _| scale * whole(10));
+                value += fraction / scale;
+            }
+        }
+        if negative {
+            value = -value;
+        }
+        let number = NumberData::from(value);
+        if !number.fits(self.width) {
+            let diagnostic = Diagnostic::error(
+                Code::NumberOverflow,
+                format!("number does not fit in {} bits", self.width),
+            )
+            .label(start.to(self.cursor), format!("this is {}", number))
+            .note({
+                let limit = (0..self.width).fold(whole(1), |limit, _| limit * whole(2));
+                format!(
+                    "{} bits hold -{} up to {}",
+                    self.width,
+                    limit.clone() / whole(2),
+                    limit - whole(1)
+                )
+            });
+            self.diagnostics.push(diagnostic);
+        }
+        Token::NumberData(number)
+    }
+    /// the digits of a number into `value`, how many there were
+    /// in hex and binary the whole alphanumeric run belongs to the number, so 0b102 is one bad number
+    fn digits(&mut self, radix: u32, base: &str, value: &mut Rational) -> usize {
+        let mut count = 0;
+        let mut invalid = None;
+        while let Some(c) = self
+            .first()
+            .filter(|&c| c == '_' || c.is_ascii_digit() || radix != 10 && c.is_ascii_alphanumeric())
+        {
+            let at = self.cursor;
+            self.bump();
+            match c.to_digit(radix) {
+                Some(digit) => {
+                    let before = std::mem::replace(value, whole(0));
+                    *value = before * whole(radix) + whole(digit);
+                    count += 1;
+                }
+                None if c == '_' => {}
+                None => {
+                    invalid.get_or_insert((c, at.to(self.cursor)));
+                }
+            }
+        }
+        if let Some((c, span)) = invalid {
+            let diagnostic = Diagnostic::error(
+                Code::InvalidNumber,
+                format!("invalid digit `{}` in a {} number", c, base),
+            )
+            .label(span, "not a digit")
+            .note(match radix {
+                16 => "hex digits are 0-9 and a-f",
+                _ => "binary digits are 0 and 1",
+            });
+            self.diagnostics.push(diagnostic);
+        }
+        count
+    }
+    /// one token and one complaint for a whole run, so !@#$ is not four errors
+    fn unknown(&mut self, start: Checkpoint) -> Token {
+        self.bump();
+        while self.first().is_some_and(|c| {
+            !is_space(c) && !matches!(c, '\n' | '\r' | '"' | '\\') && !is_identifier(c)
+        }) && operator(self.rest()).is_none()
+        {
+            self.bump();
+        }
+        let text = &self.source[start.offset..self.cursor.offset];
+        let diagnostic = Diagnostic::error(
+            Code::UnknownCharacter,
+            format!(
+                "unknown character{} `{}`",
+                if text.chars().count() > 1 { "s" } else { "" },
+                text
+            ),
+        )
+        .label(start.to(self.cursor), "not part of chiplox, skipped");
+        self.diagnostics.push(diagnostic);
+        Token::Unknown(Unknown)
+    }
+}
+impl Iterator for Lexer<'_> {
+    type Item = Spanned<Token>;
+    fn next(&mut self) -> Option<Spanned<Token>> {
+        self.lookahead.pop_front().or_else(|| self.significant())
+    }
+}
+/// whitespace, newlines, comments and text that is already reported, only kept with trivia
+pub(crate) fn is_trivia(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::Whitespace(_) | Token::Newline(_) | Token::Comment(_) | Token::Unknown(_)
+    )
+}
+/// values, names and closing brackets, what a binary operator can follow
+fn ends_operand(token: &Token) -> bool {
+    matches!(
+        token,
+        Token::NumberData(_)
+            | Token::Identifier(_)
+            | Token::Data(_)
+            | Token::RParen(_)
+            | Token::RBracket(_)
+            | Token::Keyword(Keyword::True | Keyword::False)
+    )
+}
+fn is_space(c: char) -> bool {
+    c.is_whitespace() && c != '\n' && c != '\r'
+}
+fn is_identifier_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+fn is_identifier(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+/// the longest operator the text starts with and how many chars it takes, so `<=` is never `<` then `=`
+fn operator(rest: &str) -> Option<(Token, usize)> {
+    let mut chars = rest.chars().map(AtomicToken::from);
+    let first = chars.next()?;
+    let pair = match (&first, chars.next()) {
+        (AtomicToken::Bang, Some(AtomicToken::Equal)) => Token::NotEqual(NotEqual),
+        (AtomicToken::Equal, Some(AtomicToken::Equal)) => Token::EqualEqual(EqualEqual),
+        (AtomicToken::Less, Some(AtomicToken::Equal)) => Token::LessEqual(LessEqual),
+        (AtomicToken::Greater, Some(AtomicToken::Equal)) => Token::GreaterEqual(GreaterEqual),
+        (AtomicToken::Plus, Some(AtomicToken::Plus)) => Token::PlusPlus(PlusPlus),
+        _ => {
+            let token = match first {
+                AtomicToken::Plus => Token::Plus(Plus),
+                AtomicToken::Minus => Token::Minus(Minus),
+                AtomicToken::Multiply => Token::Multiply(Multiply),
+                AtomicToken::Divide => Token::Divide(Divide),
+                AtomicToken::Modulo => Token::Modulo(Modulo),
+                AtomicToken::LParen => Token::LParen(LParen),
+                AtomicToken::RParen => Token::RParen(RParen),
+                AtomicToken::Dot => Token::Dot(Dot),
+                AtomicToken::Colon => Token::Colon(Colon),
+                AtomicToken::Less => Token::Less(Less),
+                AtomicToken::Greater => Token::Greater(Greater),
+                AtomicToken::LBracket => Token::LBracket(LBracket),
+                AtomicToken::RBracket => Token::RBracket(RBracket),
+                AtomicToken::Comma => Token::Comma(Comma),
+                AtomicToken::Question => Token::Question(Question),
+                AtomicToken::Caret => Token::Caret(Caret),
+                AtomicToken::Ampersand => Token::Ampersand(Ampersand),
+                AtomicToken::Pipe => Token::Pipe(Pipe),
+                AtomicToken::Tilde => Token::Tilde(Tilde),
+                AtomicToken::Equal => Token::Equal(Equal),
+                _ => return None,
+            };
+            return Some((token, 1));
+        }
+    };
+    Some((pair, 2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// the programs from the tokens::new test
+    const SAMPLES: [&str; 2] = [
+        r#"x = 32 + 5 * 2 - 10 + 10
+
+
+    !@#!$%#^$#&^$(& )
+    "hello ""clarice"""
+    yololololo
+    seven eight nine 10 1234123412.1444;
+    // 32 + 10 - 10 + 10
+    // 42 - 10 + 10
+    // 32 + 10
+    // 42"#,
+        "x = 10
+    y = 20
+    z = 30
+
+    result = x + - ( y + ( z - x * y + z ) - x * y )
+
+    if result then
+        print result
+    else
+        print \"\"\"fail\"\"\"
+    end
+    ",
+    ];
+
+    /// variant names, plus the text for data tokens
+    fn kinds(lexer: impl Iterator<Item = Spanned<Token>>) -> Vec<String> {
+        lexer
+            .map(|token| match token.node {
+                Token::NumberData(number) => number.to_string(),
+                Token::Identifier(Identifier(name)) => name,
+                Token::Keyword(keyword) => keyword.as_str().to_uppercase(),
+                Token::Data(Data(text)) => format!("{:?}", text),
+                token => {
+                    let debug = format!("{:?}", token);
+                    debug[..debug.find('(').unwrap_or(debug.len())].to_string()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn closing_parens_are_kept() {
+        assert_eq!(
+            kinds(Lexer::new("(1 + 2)")),
+            ["LParen", "1", "Plus", "2", "RParen"]
+        );
+        assert_eq!(
+            kinds(Lexer::new("((x))")),
+            ["LParen", "LParen", "x", "RParen", "RParen"]
+        );
+    }
+
+    #[test]
+    fn samples() {
+        assert_eq!(
+            kinds(Lexer::new(SAMPLES[0])),
+            [
+                "x",
+                "Equal",
+                "32",
+                "Plus",
+                "5",
+                "Multiply",
+                "2",
+                "Minus",
+                "10",
+                "Plus",
+                "10",
+                "Modulo",
+                "Caret",
+                "Ampersand",
+                "Caret",
+                "LParen",
+                "Ampersand",
+                "RParen",
+                "\"hello \\\"clarice\\\"\"",
+                "yololololo",
+                "seven",
+                "eight",
+                "nine",
+                "10",
+                "3085308530361/2500"
+            ]
+        );
+        let mut lexer = Lexer::new(SAMPLES[0]);
+        lexer.by_ref().for_each(drop);
+        let unknown: Vec<&str> = lexer
+            .diagnostics()
+            .0
+            .iter()
+            .map(|diagnostic| &SAMPLES[0][diagnostic.span().unwrap().range()])
+            .collect();
+        // the big number is too wide for 16 bits
+        assert_eq!(unknown, ["!@#!$", "#", "$#", "$", "1234123412.1444", ";"]);
+        assert_eq!(
+            kinds(Lexer::new(SAMPLES[1])),
+            [
+                "x", "Equal", "10", "y", "Equal", "20", "z", "Equal", "30", "result", "Equal", "x",
+                "Plus", "Minus", "LParen", "y", "Plus", "LParen", "z", "Minus", "x", "Multiply",
+                "y", "Plus", "z", "RParen", "Minus", "x", "Multiply", "y", "RParen", "IF",
+                "result", "THEN", "PRINT", "result", "ELSE", "PRINT", "\"fail\"", "END"
+            ]
+        );
+    }
+
+    #[test]
+    fn trivia_is_lossless() {
+        let tricky = [
+            "",
+            " ",
+            "\r\n\t x\\\ny",
+            "\"open",
+            "é + 1.",
+            "a\\",
+            "\"\"\"\"",
+        ];
+        for source in SAMPLES.iter().chain(tricky.iter()) {
+            let mut end = 0;
+            let mut rebuilt = String::new();
+            for token in Lexer::new(source).with_trivia() {
+                assert_eq!(
+                    token.span.start, end,
+                    "gap before {:?} in {:?}",
+                    token, source
+                );
+                assert_eq!(token.span, Span::new(source, token.span.range()));
+                end = token.span.end;
+                rebuilt.push_str(&source[token.span.range()]);
+            }
+            assert_eq!(&rebuilt, source);
+        }
+        assert_eq!(
+            kinds(Lexer::new("a \t\r\n\nb").with_trivia()),
+            ["a", "Whitespace", "Newline", "Newline", "b"]
+        );
+        assert_eq!(
+            kinds(Lexer::new("1 / 2 // half\r\n").with_trivia()),
+            [
+                "1",
+                "Whitespace",
+                "Divide",
+                "Whitespace",
+                "2",
+                "Whitespace",
+                "Comment",
+                "Newline"
+            ]
+        );
+    }
+
+    #[test]
+    fn operators_munch() {
+        assert_eq!(
+            kinds(Lexer::new("a<=b<c!=d==e=f>=g>h ++i+j .:[],?^&|~")),
+            [
+                "a",
+                "LessEqual",
+                "b",
+                "Less",
+                "c",
+                "NotEqual",
+                "d",
+                "EqualEqual",
+                "e",
+                "Equal",
+                "f",
+                "GreaterEqual",
+                "g",
+                "Greater",
+                "h",
+                "PlusPlus",
+                "i",
+                "Plus",
+                "j",
+                "Dot",
+                "Colon",
+                "LBracket",
+                "RBracket",
+                "Comma",
+                "Question",
+                "Caret",
+                "Ampersand",
+                "Pipe",
+                "Tilde"
+            ]
+        );
+        // === is == then =, +++ is ++ then +, a lone ! is still unknown
+        assert_eq!(
+            kinds(Lexer::new("=== +++")),
+            ["EqualEqual", "Equal", "PlusPlus", "Plus"]
+        );
+        assert_eq!(kinds(Lexer::new("!x").with_trivia()), ["Unknown", "x"]);
+    }
+
+    #[test]
+    fn keywords() {
+        assert_eq!(
+            kinds(Lexer::new("if iffy then_ fn not true False end")),
+            ["IF", "iffy", "then_", "FN", "NOT", "TRUE", "False", "END"]
+        );
+    }
+
+    #[test]
+    fn comments() {
+        let source = "a // b\n/* c /* d */ e */ f /// g\n//// h\n/**/";
+        assert_eq!(
+            kinds(Lexer::new(source).with_trivia()),
+            [
+                "a",
+                "Whitespace",
+                "Comment",
+                "Newline",
+                "Comment",
+                "Whitespace",
+                "f",
+                "Whitespace",
+                "DocComment",
+                "Newline",
+                "Comment",
+                "Newline",
+                "Comment"
+            ]
+        );
+        let docs: Vec<Token> = Lexer::new(source)
+            .filter(|token| matches!(token.node, Token::DocComment(_)))
+            .map(|token| token.node)
+            .collect();
+        assert_eq!(docs, [Token::DocComment(DocComment(" g".to_string()))]);
+        let mut lexer = Lexer::new("x /* a /* b */\n");
+        assert_eq!(kinds(lexer.by_ref()), ["x"]);
+        assert_eq!(lexer.diagnostics().0[0].code, Code::UnterminatedComment);
+        assert_eq!(
+            lexer.diagnostics().0[0].span(),
+            Some(Span::new("x /* a /* b */\n", 2..4))
+        );
+        let mut lexer = Lexer::new("/* open").partial();
+        assert_eq!(lexer.next(), None);
+        assert_eq!(lexer.incomplete(), Some(Checkpoint::default()));
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(
+            kinds(Lexer::new(r#""" "a""b" "é""#)),
+            ["\"\"", "\"a\\\"b\"", "\"é\""]
+        );
+        let source = "x \"never\n";
+        let mut lexer = Lexer::new(source);
+        assert_eq!(kinds(Lexer::new(source)), ["x", "\"never\\n\""]);
+        lexer.by_ref().for_each(drop);
+        let diagnostic = &lexer.diagnostics().0[0];
+        assert_eq!(diagnostic.code, Code::UnterminatedString);
+        assert_eq!(diagnostic.span(), Some(Span::new(source, 2..3)));
+        assert_eq!(diagnostic.suggestions[0].span.start, 8);
+    }
+
+    #[test]
+    fn escapes_and_raw_strings() {
+        assert_eq!(
+            kinds(Lexer::new(
+                r##""a\n\t\"\\\u{e9}\u{1F600}" r"\n{x}" r#"say "hi""# "{{}}""##
+            )),
+            [
+                "\"a\\n\\t\\\"\\\\é😀\"",
+                "\"\\\\n{x}\"",
+                "\"say \\\"hi\\\"\"",
+                "\"{}\""
+            ]
+        );
+        let source = r#""\q \u{110000} \u{zz} \u{41" 1"#;
+        let mut lexer = Lexer::new(source);
+        assert_eq!(
+            kinds(lexer.by_ref()),
+            ["\"\\\\q \\\\u{110000} \\\\u{zz} \\\\u{41\"", "1"]
+        );
+        let escapes: Vec<&str> = lexer
+            .diagnostics()
+            .0
+            .iter()
+            .map(|diagnostic| {
+                assert_eq!(diagnostic.code, Code::InvalidEscape);
+                &source[diagnostic.span().unwrap().range()]
+            })
+            .collect();
+        assert_eq!(escapes, ["\\q", "\\u{110000}", "\\u{zz}", "\\u{41"]);
+        let mut lexer = Lexer::new(r##"r#"open"##);
+        lexer.by_ref().for_each(drop);
+        assert_eq!(lexer.diagnostics().0[0].suggestions[0].replacement, "\"#");
+    }
+
+    #[test]
+    fn triple_quoted_strings() {
+        assert_eq!(
+            kinds(Lexer::new("\"\"\"fail\"\"\" \"\"\"two\n\"lines\"\n\"\"\"")),
+            ["\"fail\"", "\"two\\n\\\"lines\\\"\\n\""]
+        );
+        let mut lexer = Lexer::new("\"\"\"open\n").partial();
+        assert_eq!(lexer.next(), None);
+        assert_eq!(lexer.incomplete(), Some(Checkpoint::default()));
+        let mut lexer = Lexer::new("\"\"\"open\"\"");
+        lexer.by_ref().for_each(drop);
+        assert_eq!(
+            lexer.diagnostics().0[0].suggestions[0].replacement,
+            "\"\"\""
+        );
+    }
+
+    #[test]
+    fn interpolation_is_concatenation() {
+        let source = "\"score: {x + 1}!\" \"{y}\"";
+        assert_eq!(
+            kinds(Lexer::new(source)),
+            [
+                "\"score: \"",
+                "PlusPlus",
+                "LParen",
+                "x",
+                "Plus",
+                "1",
+                "RParen",
+                "PlusPlus",
+                "\"!\"",
+                "\"\"",
+                "PlusPlus",
+                "LParen",
+                "y",
+                "RParen",
+                "PlusPlus",
+                "\"\""
+            ]
+        );
+        let spans: Vec<&str> = Lexer::new(source)
+            .take(9)
+            .map(|token| &source[token.span.range()])
+            .collect();
+        assert_eq!(
+            spans,
+            ["\"score: ", "{", "{", "x", "+", "1", "}", "}", "!\""]
+        );
+        // with trivia it stays one token
+        assert_eq!(kinds(Lexer::new("\"a{x}\"").with_trivia()), ["\"a{x}\""]);
+        let mut lexer = Lexer::new("\"{x\" \"{}\"");
+        lexer.by_ref().for_each(drop);
+        let codes: Vec<Code> = lexer
+            .diagnostics()
+            .0
+            .iter()
+            .map(|diagnostic| diagnostic.code)
+            .collect();
+        assert_eq!(codes, [Code::UnclosedDelimiter, Code::ExpectedExpression]);
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(
+            kinds(Lexer::new("0 007 1.50 2.25 1. .5 10abc")),
+            ["0", "7", "3/2", "9/4", "1", "Dot", "Dot", "5", "10", "abc"]
+        );
+    }
+
+    #[test]
+    fn number_forms() {
+        assert_eq!(
+            kinds(Lexer::new("0x2A 0XfF 0b1010_0101 1_000 0.1 0.000_5 0x_1")),
+            ["42", "255", "165", "1000", "1/10", "1/2000", "1"]
+        );
+        let source = "0x 0b102 0xAg 0x1.5";
+        let mut lexer = Lexer::new(source);
+        assert_eq!(kinds(lexer.by_ref()), ["0", "2", "10", "1", "Dot", "5"]);
+        let flagged: Vec<(Code, &str)> = lexer
+            .diagnostics()
+            .0
+            .iter()
+            .map(|diagnostic| (diagnostic.code, &source[diagnostic.span().unwrap().range()]))
+            .collect();
+        assert_eq!(
+            flagged,
+            [
+                (Code::InvalidNumber, "0x"),
+                (Code::InvalidNumber, "2"),
+                (Code::InvalidNumber, "g")
+            ]
+        );
+    }
+
+    #[test]
+    fn negative_literals() {
+        // a `-` right before a digit signs it unless it follows something it could subtract from
+        assert_eq!(kinds(Lexer::new("--0")), ["Minus", "0"]);
+        assert_eq!(kinds(Lexer::new("x - -1")), ["x", "Minus", "-1"]);
+        assert_eq!(
+            kinds(Lexer::new("-(1)")),
+            ["Minus", "LParen", "1", "RParen"]
+        );
+        assert_eq!(kinds(Lexer::new("1-1")), ["1", "Minus", "1"]);
+        assert_eq!(
+            kinds(Lexer::new("(-1)-1 - 1 -0x2A -1.5 return -1")),
+            [
+                "LParen", "-1", "RParen", "Minus", "1", "Minus", "1", "Minus", "42", "Minus",
+                "3/2", "RETURN", "-1"
+            ]
+        );
+        let source = "x = -1";
+        let token = Lexer::new(source).nth(2).unwrap();
+        assert_eq!(&source[token.span.range()], "-1");
+        // comments and whitespace do not change which it is
+        assert_eq!(
+            kinds(Lexer::new("f /* */ -1").with_trivia()),
+            ["f", "Whitespace", "Comment", "Whitespace", "Minus", "1"]
+        );
+    }
+
+    #[test]
+    fn numbers_must_fit() {
+        let overflows = |source: &str, bits: u32| {
+            let mut lexer = Lexer::new(source).width(bits);
+            lexer.by_ref().for_each(drop);
+            lexer
+                .diagnostics()
+                .0
+                .iter()
+                .map(|diagnostic| diagnostic.code)
+                .collect::<Vec<_>>()
+        };
+        assert!(overflows("65535 0xFFFF 65535.5", 16).is_empty());
+        assert_eq!(overflows("65536", 16), [Code::NumberOverflow]);
+        assert!(overflows("255", 8).is_empty());
+        assert_eq!(
+            overflows("0x100 0b1_0000_0000", 8),
+            [Code::NumberOverflow; 2]
+        );
+        let mut lexer = Lexer::new("256").width(8);
+        lexer.by_ref().for_each(drop);
+        assert_eq!(
+            lexer.diagnostics().0[0].notes,
+            ["8 bits hold -128 up to 255"]
+        );
+    }
+
+    #[test]
+    fn lookahead() {
+        let mut lexer = Lexer::new("1 + 2");
+        assert_eq!(lexer.peek_nth(2).map(|token| token.span.start), Some(4));
+        assert!(matches!(
+            lexer.peek().map(|token| &token.node),
+            Some(Token::NumberData(_))
+        ));
+        assert!(lexer.peek_nth(3).is_none());
+        assert_eq!(kinds(lexer), ["1", "Plus", "2"]);
+    }
+
+    #[test]
+    fn resumes_unfinished_strings() {
+        let mut source = String::from("x + \"first\n");
+        let mut lexer = Lexer::new(&source).partial();
+        assert_eq!(kinds(Lexer::new(&source).partial()), ["x", "Plus"]);
+        lexer.by_ref().for_each(drop);
+        let checkpoint = lexer.incomplete().expect("string is still open");
+        assert!(lexer.diagnostics().is_empty());
+        source.push_str("second\" - 1\n");
+        let mut lexer = Lexer::resume(&source, checkpoint).partial();
+        let string = lexer.next().unwrap();
+        assert!(matches!(&string.node, Token::Data(Data(text)) if text == "first\nsecond"));
+        assert_eq!(
+            (string.span.start, string.span.line, string.span.col),
+            (4, 1, 5)
+        );
+        assert_eq!(kinds(lexer), ["Minus", "1"]);
+    }
+
+    #[test]
+    fn resumes_continued_lines() {
+        let mut source = String::from("1 + \\\n");
+        let mut lexer = Lexer::new(&source).partial();
+        assert_eq!(kinds(Lexer::new(&source).partial()), ["1", "Plus"]);
+        lexer.by_ref().for_each(drop);
+        let checkpoint = lexer.incomplete().expect("line is continued");
+        source.push_str("2\n");
+        let lexer = Lexer::resume(&source, checkpoint).partial();
+        let tokens: Vec<Spanned<Token>> = lexer.collect();
+        assert_eq!(tokens[0].span, Span::new(&source, 6..7));
+        assert_eq!(tokens[0].span.line, 2);
+        // not partial, so the same line is just a lone backslash
+        assert_eq!(
+            kinds(Lexer::new("1 \\").with_trivia()),
+            ["1", "Whitespace", "Unknown"]
+        );
+        let mut lexer = Lexer::new("1 \\").partial();
+        lexer.by_ref().for_each(drop);
+        assert_eq!(lexer.incomplete().map(|at| at.offset), Some(2));
+    }
+
+    #[test]
+    fn resumes_after_an_operand() {
+        let mut source = String::from("x \\\n");
+        let mut lexer = Lexer::new(&source).partial();
+        let mut tokens = kinds(lexer.by_ref());
+        let checkpoint = lexer.incomplete().expect("line is continued");
+        source.push_str("-1\n");
+        tokens.extend(kinds(Lexer::resume(&source, checkpoint).partial()));
+        assert_eq!(tokens, ["x", "Minus", "1"]);
+    }
+}
@@ -1,8 +1,14 @@
+// exact decimals, gmp when the rug feature is on, pure rust otherwise
+#[cfg(not(feature = "rug"))]
+use num_rational::BigRational as Rational;
+#[cfg(feature = "rug")]
 use rug::Rational;
-use std::{
-    fmt::Debug,
-    str::{Chars, FromStr},
-};
+use std::{fmt::Debug, str::Chars};
+
+mod lexer;
+
+pub use self::lexer::{Checkpoint, Lexer};
+use super::{diagnostics::Diagnostics, span::Spanned};
 fn variant_eq<T>(lhs: &T, rhs: &T) -> bool {
     std::mem::discriminant(lhs) == std::mem::discriminant(rhs)
 }
@@ -10,6 +16,7 @@ fn variant_eq<T>(lhs: &T, rhs: &T) -> bool {
 pub enum Token {
     NumberData(NumberData),
     Identifier(Identifier),
+    Keyword(Keyword),
     Data(Data),
     Whitespace(Whitespace),
     Plus(Plus),
@@ -19,14 +26,30 @@ pub enum Token {
     Modulo(Modulo),
     LParen(LParen),
     RParen(RParen),
+    Dot(Dot),
+    Colon(Colon),
+    Less(Less),
+    Greater(Greater),
+    LBracket(LBracket),
+    RBracket(RBracket),
+    Comma(Comma),
+    Question(Question),
+    Caret(Caret),
+    Ampersand(Ampersand),
+    Pipe(Pipe),
+    Tilde(Tilde),
+    NotEqual(NotEqual),
+    EqualEqual(EqualEqual),
+    LessEqual(LessEqual),
+    GreaterEqual(GreaterEqual),
+    Equal(Equal),
+    PlusPlus(PlusPlus),
     Newline(Newline),
+    Comment(Comment),
+    DocComment(DocComment),
     Unknown(Unknown),
 }
 
-// eof, dot, Op, any, maybe :<>[],?,String Star Slash Percent Caret Ampersand Pipe Tilde Neq Lt
-// but if its not needed, just do it in standard:library
-// and use identifier in the tokenizer}
-
 // #[cfg(test)]
 // mod tests {
 //     use super::*;
@@ -43,38 +66,6 @@ pub enum Token {
 //     }
 // }
 
-#[test]
-pub(crate) fn new() {
-    let x = r#"x = 32 + 5 * 2 - 10 + 10
-
-
-    !@#!$%#^$#&^$(& )
-    "hello ""clarice"""
-    yololololo
-    seven eight nine 10 1234123412.1444;
-    // 32 + 10 - 10 + 10
-    // 42 - 10 + 10
-    // 32 + 10
-    // 42"#;
-    let y = tokenize(x);
-    println!("{:#?}", y.0);
-
-    let z = tokenize(
-        "x = 10
-    y = 20
-    z = 30
-
-    result = x + - ( y + ( z - x * y + z ) - x * y )
-
-    if result then
-        print result
-    else
-        print \"\"\"fail\"\"\"
-    end
-    ",
-    );
-    println!("{:#?}", z.0);
-}
 pub enum AtomicToken {
     Whitespace,
     Plus,
@@ -87,6 +78,19 @@ pub enum AtomicToken {
     Newline,
     DQuote,
     Dot,
+    Colon,
+    Less,
+    Greater,
+    LBracket,
+    RBracket,
+    Comma,
+    Question,
+    Caret,
+    Ampersand,
+    Pipe,
+    Tilde,
+    Equal,
+    Bang,
     Unknown,
 }
 impl From<char> for AtomicToken {
@@ -103,6 +107,19 @@ impl From<char> for AtomicToken {
             '\n' | '\r' => AtomicToken::Newline,
             '"' => AtomicToken::DQuote,
             '.' => AtomicToken::Dot,
+            ':' => AtomicToken::Colon,
+            '<' => AtomicToken::Less,
+            '>' => AtomicToken::Greater,
+            '[' => AtomicToken::LBracket,
+            ']' => AtomicToken::RBracket,
+            ',' => AtomicToken::Comma,
+            '?' => AtomicToken::Question,
+            '^' => AtomicToken::Caret,
+            '&' => AtomicToken::Ampersand,
+            '|' => AtomicToken::Pipe,
+            '~' => AtomicToken::Tilde,
+            '=' => AtomicToken::Equal,
+            '!' => AtomicToken::Bang,
             _ => AtomicToken::Unknown,
         }
     }
@@ -120,171 +137,104 @@ impl From<AtomicToken> for char {
             AtomicToken::Newline => '\n',
             AtomicToken::DQuote => '"',
             AtomicToken::Dot => '.',
+            AtomicToken::Colon => ':',
+            AtomicToken::Less => '<',
+            AtomicToken::Greater => '>',
+            AtomicToken::LBracket => '[',
+            AtomicToken::RBracket => ']',
+            AtomicToken::Comma => ',',
+            AtomicToken::Question => '?',
+            AtomicToken::Caret => '^',
+            AtomicToken::Ampersand => '&',
+            AtomicToken::Pipe => '|',
+            AtomicToken::Tilde => '~',
+            AtomicToken::Equal => '=',
+            AtomicToken::Bang => '!',
             AtomicToken::Whitespace | AtomicToken::Unknown => ' ',
         }
     }
 }
-enum DataToken {
-    Number,
-    Data,
-    Identifier,
-}
 #[derive(Debug, Clone, PartialEq)]
-pub struct Tokens(pub(crate) Vec<Token>);
+pub struct Tokens(pub(crate) Vec<Spanned<Token>>);
 
+/// tokenize, dropping whatever it had to skip or guess at
 pub(crate) fn tokenize(str: &str) -> Tokens {
-    let mut tokens = Vec::new();
-    let mut chars = str.chars();
-    let mut char = chars.next();
-    let mut is_next_char = false;
-    let mut is_complex = false;
-    while char.is_some() {
-        // tokens.push(tokenize(&char, &chars)); // todo figure out how to do this
-        let mut char_unwrapped = char.unwrap();
-        tokens.push(match AtomicToken::from(char_unwrapped) {
-            AtomicToken::Plus => Token::Plus(Plus),
-            AtomicToken::Minus => Token::Minus(Minus),
-            AtomicToken::Multiply => Token::Multiply(Multiply),
-            AtomicToken::Divide => Token::Divide(Divide),
-            AtomicToken::Modulo => Token::Modulo(Modulo),
-            AtomicToken::LParen => Token::LParen(LParen),
-            AtomicToken::RParen => Token::RParen(RParen),
-            AtomicToken::Newline => Token::Newline(Newline), // todo compress newlines
-            AtomicToken::Whitespace => Token::Whitespace(Whitespace()), // todo compress whitespaces
-            atomic_token => {
-                is_complex = true;
-                let mut string = String::new();
-                let mut data_type = if char_unwrapped.is_numeric() {
-                    DataToken::Number
-                } else if variant_eq(&atomic_token, &AtomicToken::DQuote) {
-                    char = chars.next(); // skip leading "
-                    if char.is_some() {
-                        char_unwrapped = char.unwrap();
-                    } else {
-                        break;
-                    }
-                    DataToken::Data
-                } else {
-                    DataToken::Identifier
-                };
-                let mut result = Token::Unknown(Unknown);
-
-                while !is_next_char {
-                    result = match data_type {
-                        DataToken::Number => {
-                            print!("number|\n");
-                            let mut decimal_point = 0;
-                            let mut right_of_decimal_separator = false;
-                            string.push(char_unwrapped);
-                            char = chars.next();
-                            while char.is_some() {
-                                char_unwrapped = char.unwrap();
-                                match char_unwrapped {
-                                    c if c.is_numeric() => {
-                                        string.push(c);
-                                        if right_of_decimal_separator {
-                                            decimal_point += 1;
-                                        }
-                                    }
-                                    c if variant_eq(&c.into(), &AtomicToken::Dot)
-                                        && !right_of_decimal_separator =>
-                                    {
-                                        right_of_decimal_separator = true;
-                                    }
-                                    c => {
-                                        if variant_eq(&c.into(), &AtomicToken::Unknown) {
-                                            data_type = DataToken::Identifier;
-                                        }
-                                        break;
-                                    }
-                                }
-                                char = chars.next();
-                            }
-                            if variant_eq(&data_type, &DataToken::Number) {
-                                is_next_char = true;
-                                Token::NumberData(NumberData(
-                                    Rational::from_str(
-                                        &format!(
-                                            "{numerator}/1{denominator_zeroes}",
-                                            numerator = string,
-                                            denominator_zeroes = &"0".repeat(decimal_point)[..]
-                                        )[..],
-                                    )
-                                    .unwrap()
-                                    .to_string(),
-                                ))
-                            } else {
-                                continue;
-                            }
-                        }
-
-                        DataToken::Identifier => {
-                            string.push(char_unwrapped);
-                            char = chars.next();
-                            while char.is_some() {
-                                char_unwrapped = char.unwrap();
-                                if !variant_eq(&char_unwrapped.into(), &AtomicToken::Unknown) {
-                                    break;
-                                }
-                                string.push(char_unwrapped);
-                                char = chars.next();
-                            }
-                            is_next_char = true;
-                            Token::Identifier(Identifier(string.clone()))
-                        }
-
-                        DataToken::Data => {
-                            let mut escaped = false;
-                            while char.is_some() {
-                                char_unwrapped = char.unwrap();
-                                if escaped {
-                                    if !variant_eq(&char_unwrapped.into(), &AtomicToken::DQuote) {
-                                        break;
-                                    }
-                                } else if variant_eq(&char_unwrapped.into(), &AtomicToken::DQuote) {
-                                    escaped = !escaped;
-                                    char = chars.next();
-                                    continue;
-                                }
-                                string.push(char_unwrapped);
-                                char = chars.next();
-                            }
-                            // "hello ""clarice"""
-                            // hello ""clarice""".is_some'1
-                            // hello "clarice"""
-                            // hello "clarice""
-                            // hello "clarice"
-                            is_next_char = true;
-                            Token::Data(Data(string.clone()))
-                        }
-                    };
-                }
-                result
-            }
-        });
-        if !is_complex & !is_next_char {
-            char = chars.next();
-        }
-        is_next_char = false;
-        is_complex = false;
-    }
-    tokens.retain(|t| -> bool {
-        !variant_eq(t, &Token::Whitespace(Whitespace()))
-            && !variant_eq(t, &Token::Newline(Newline))
-            && !variant_eq(t, &Token::Unknown(Unknown))
-    });
-    while tokens.last().is_some() && variant_eq(tokens.last().unwrap(), &Token::RParen(RParen)) {
-        tokens.pop();
-    }
-    Tokens(tokens)
+    tokenize_with(str, &mut Diagnostics::default())
+}
+/// tokenize, reporting whatever it has to skip or guess at
+pub(crate) fn tokenize_with(str: &str, diagnostics: &mut Diagnostics) -> Tokens {
+    let mut lexer = Lexer::new(str);
+    let tokens = Tokens(lexer.by_ref().collect());
+    diagnostics.extend(lexer.take_diagnostics());
+    tokens
 }
 #[derive(Debug, Clone, PartialEq)]
-pub struct NumberData(String);
-pub struct Number<T>(T);
+pub struct NumberData(Rational);
 #[derive(Debug, Clone, PartialEq)]
 pub struct Identifier(String);
 #[derive(Debug, Clone, PartialEq)]
 pub struct Data(String);
+#[derive(Copy, Debug, Clone, PartialEq, Eq)]
+pub enum Keyword {
+    If,
+    Then,
+    Else,
+    End,
+    Print,
+    While,
+    For,
+    Fn,
+    Return,
+    And,
+    Or,
+    Not,
+    True,
+    False,
+}
+/// reserved words, an identifier spelled like one of these is the keyword instead
+const KEYWORDS: [(&str, Keyword); 14] = [
+    ("if", Keyword::If),
+    ("then", Keyword::Then),
+    ("else", Keyword::Else),
+    ("end", Keyword::End),
+    ("print", Keyword::Print),
+    ("while", Keyword::While),
+    ("for", Keyword::For),
+    ("fn", Keyword::Fn),
+    ("return", Keyword::Return),
+    ("and", Keyword::And),
+    ("or", Keyword::Or),
+    ("not", Keyword::Not),
+    ("true", Keyword::True),
+    ("false", Keyword::False),
+];
+impl Keyword {
+    pub fn lookup(name: &str) -> Option<Keyword> {
+        KEYWORDS
+            .iter()
+            .find(|(word, _)| *word == name)
+            .map(|(_, keyword)| *keyword)
+    }
+    pub fn as_str(&self) -> &'static str {
+        KEYWORDS
+            .iter()
+            .find(|(_, keyword)| keyword == self)
+            .map(|(word, _)| *word)
+            .expect("every keyword is in the table")
+    }
+}
+/// `// text` up to the end of the line or `/* text */`, without the slashes
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment(String);
+/// `/// text`, not trivia, the parser hands it to the declaration below
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocComment(String);
+impl DocComment {
+    /// the text with the one space after `///` dropped
+    pub fn text(&self) -> &str {
+        self.0.strip_prefix(' ').unwrap_or(&self.0)
+    }
+}
 #[derive(Copy, Debug, Clone, PartialEq)]
 pub struct Whitespace();
 /// broken
@@ -313,6 +263,42 @@ pub struct LParen;
 #[derive(Copy, Debug, Clone, PartialEq)]
 pub struct RParen;
 #[derive(Copy, Debug, Clone, PartialEq)]
+pub struct Dot;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct Colon;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct Less;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct Greater;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct LBracket;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct RBracket;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct Comma;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct Question;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct Caret;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct Ampersand;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct Pipe;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct Tilde;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct NotEqual;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct EqualEqual;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct LessEqual;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct GreaterEqual;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct Equal;
+#[derive(Copy, Debug, Clone, PartialEq)]
+pub struct PlusPlus;
+#[derive(Copy, Debug, Clone, PartialEq)]
 pub struct Newline;
 /// broken
 impl From<&mut Chars<'_>> for Newline {
@@ -329,47 +315,46 @@ impl From<&mut Chars<'_>> for Newline {
 pub struct Unknown;
 impl From<NumberData> for Token {
     fn from(val: NumberData) -> Self {
-        Token::NumberData(NumberData::from(val.0))
-    }
-}
-impl From<Number<String>> for Token {
-    fn from(val: Number<String>) -> Self {
-        Token::NumberData(NumberData::from(val.0))
+        Token::NumberData(val)
     }
 }
-impl From<String> for NumberData {
-    fn from(val: String) -> Self {
+impl From<Rational> for NumberData {
+    fn from(val: Rational) -> Self {
         NumberData(val)
     }
 }
-impl<T> From<Number<T>> for rug::Rational
-where
-    T: std::fmt::Display,
-{
-    fn from(val: Number<T>) -> Self {
-        <rug::Rational as FromStr>::from_str(&val.0.to_string()).unwrap()
+impl NumberData {
+    pub fn value(&self) -> &Rational {
+        &self.0
     }
-}
-impl<T> From<NumberData> for Number<T> {
-    fn from(x: NumberData) -> Self {
-        x.into()
+    /// whether it fits in `bits`, unsigned or two's complement when negative
+    pub(crate) fn fits(&self, bits: u32) -> bool {
+        let limit = (0..bits).fold(whole(1), |limit, _| limit * whole(2));
+        self.0 < limit && self.0 >= -limit / whole(2)
     }
 }
-impl<T> From<Number<T>> for NumberData
-where
-    T: std::fmt::Display,
-{
-    fn from(val: Number<T>) -> Self {
-        let mut str = String::new();
-        str.push_str(&val.0.to_string());
-        NumberData(str)
+impl std::fmt::Display for NumberData {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
     }
 }
+/// a whole number in whichever rational the build uses
+pub(crate) fn whole(n: u32) -> Rational {
+    #[cfg(feature = "rug")]
+    return Rational::from(n);
+    #[cfg(not(feature = "rug"))]
+    return Rational::from_integer(n.into());
+}
 impl From<Identifier> for Token {
     fn from(val: Identifier) -> Self {
         Token::Identifier(val)
     }
 }
+impl Identifier {
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
 impl From<String> for Identifier {
     fn from(val: String) -> Self {
         Identifier(val)
@@ -2,9 +2,23 @@
 #![feature(type_ascription)]
 #![feature(unboxed_closures)]
 #![feature(unwrap_infallible)]
+#[cfg(feature = "lang")]
 extern crate num_traits;
+#[cfg(feature = "bevy")]
 mod app;
+#[cfg(feature = "bevy")]
+pub use app::SyncBench;
+pub mod chip;
+#[cfg(feature = "lang")]
 pub mod lang;
+#[cfg(feature = "tui")]
+pub mod tui;
+#[cfg(feature = "bevy")]
 pub fn main() {
-    app::new();
+    // chiploxide [PATH_TO_CHIP8_FILE]... one machine per rom, side by side
+    let roms = std::env::args()
+        .skip(1)
+        .map(|path| std::fs::read(&path).unwrap_or_else(|e| panic!("{}: {}", path, e)))
+        .collect();
+    app::new(roms);
 }
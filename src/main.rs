@@ -1,3 +1,3 @@
 fn main() {
-    crate::main();
+    chiploxide::main();
 }
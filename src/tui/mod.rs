@@ -0,0 +1,262 @@
+// terminal frontend for the headless chip, for when there is no window (ssh, no gpu)
+// screen is drawn with half blocks so 64x32 pixels fit in 64x16 cells
+use crossterm::{
+    cursor,
+    event::{self, Event, KeyCode, KeyEvent, KeyModifiers},
+    queue,
+    style::{Color, Print, ResetColor, SetBackgroundColor, SetForegroundColor},
+    terminal::{self, ClearType},
+};
+use std::{
+    fs,
+    io::{self, Stdout, Write},
+    time::{Duration, Instant},
+};
+
+use crate::chip::{
+    clock::{Clock, Speed},
+    payload::Payload,
+    screen::{SCREEN_X, SCREEN_Y},
+    Cpu,
+};
+
+const FRAME_INTERVAL: Duration = Duration::from_micros(1_000_000 / 60);
+/// terminals only send presses (and repeats), so a key counts as released
+/// once it has not been seen for this long
+const KEY_RELEASE: Duration = Duration::from_millis(250);
+const DISASSEMBLY_LINES: usize = 12;
+const PANE_X: u16 = SCREEN_X as u16 + 3;
+
+pub fn new(rom: Option<&str>) -> io::Result<()> {
+    let rom = match rom {
+        Some(path) => fs::read(path)?,
+        None => Vec::new(),
+    };
+    play(&Payload {
+        rom,
+        ..Payload::default()
+    })
+}
+/// also what an exported runner calls with its embedded payload
+pub fn play(payload: &Payload) -> io::Result<()> {
+    let mut cpu = if payload.rom.is_empty() {
+        Cpu::new()
+    } else {
+        Cpu::load(&payload.rom)
+    };
+    cpu.quirks = payload.profile.quirks();
+    let mut terminal = Terminal::new()?;
+    run(&mut terminal, cpu, payload)
+}
+fn run(terminal: &mut Terminal, mut cpu: Cpu, payload: &Payload) -> io::Result<()> {
+    let mut keypad = Keypad::default();
+    let mut clock = Clock::default();
+    let mut last_frame = Instant::now();
+    loop {
+        while event::poll(FRAME_INTERVAL.saturating_sub(last_frame.elapsed()))? {
+            if let Event::Key(key) = event::read()? {
+                if is_quit(&key) {
+                    return Ok(());
+                }
+                match key.code {
+                    KeyCode::Char(c) => keypad.press(payload.key(c)),
+                    KeyCode::F(f) => clock_key(&mut clock, f),
+                    _ => (),
+                }
+            }
+        }
+        keypad.release_stale(&mut cpu);
+        if last_frame.elapsed() >= FRAME_INTERVAL {
+            cpu.advance(clock.tick(last_frame.elapsed()));
+            last_frame = Instant::now();
+            draw(&mut terminal.0, &cpu, &clock, payload)?;
+        }
+    }
+}
+/// same bindings as app::keyboard::clock_keys
+fn clock_key(clock: &mut Clock, f: u8) {
+    match f {
+        1..=4 => clock.preset(f as usize - 1),
+        5 => clock.toggle_pause(),
+        6 => clock.advance(),
+        7 => clock.toggle_slow(),
+        8 => clock.toggle_turbo(),
+        9 => clock.step(),
+        _ => (),
+    }
+}
+fn is_quit(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Esc
+        || (key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL))
+}
+/// raw mode + alternate screen, put back on drop (including panics)
+struct Terminal(Stdout);
+impl Terminal {
+    fn new() -> io::Result<Terminal> {
+        let mut stdout = io::stdout();
+        terminal::enable_raw_mode()?;
+        crossterm::execute!(
+            stdout,
+            terminal::EnterAlternateScreen,
+            terminal::Clear(ClearType::All),
+            cursor::Hide
+        )?;
+        Ok(Terminal(stdout))
+    }
+}
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        let _ = crossterm::execute!(self.0, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+/// chip keys in keypad order, for drawing the keypad and the key help
+const KEYPAD: [[usize; 4]; 4] = [
+    [1, 2, 3, 0xC],
+    [4, 5, 6, 0xD],
+    [7, 8, 9, 0xE],
+    [0xA, 0, 0xB, 0xF],
+];
+#[derive(Debug, Default)]
+struct Keypad([Option<Instant>; 16]);
+impl Keypad {
+    fn press(&mut self, key: Option<usize>) {
+        if let Some(key) = key {
+            self.0[key] = Some(Instant::now());
+        }
+    }
+    fn release_stale(&mut self, cpu: &mut Cpu) {
+        for (key, seen) in self.0.iter_mut().enumerate() {
+            if seen.is_some_and(|seen| seen.elapsed() > KEY_RELEASE) {
+                *seen = None;
+            }
+            cpu.keys[key] = seen.is_some();
+        }
+    }
+}
+fn draw(out: &mut Stdout, cpu: &Cpu, clock: &Clock, payload: &Payload) -> io::Result<()> {
+    draw_screen(out, cpu, payload)?;
+    draw_clock(out, clock)?;
+    draw_registers(out, cpu)?;
+    draw_disassembly(out, cpu)?;
+    out.flush()
+}
+fn rgb(color: u32) -> Color {
+    Color::Rgb {
+        r: (color >> 16) as u8,
+        g: (color >> 8) as u8,
+        b: color as u8,
+    }
+}
+/// two pixel rows per terminal row, lit pixels are the foreground
+fn draw_screen(out: &mut Stdout, cpu: &Cpu, payload: &Payload) -> io::Result<()> {
+    let border = "─".repeat(SCREEN_X);
+    queue!(out, cursor::MoveTo(0, 0), Print(format!("┌{}┐", border)))?;
+    for row in 0..SCREEN_Y / 2 {
+        let mut line = format!(
+            "│{}{}",
+            SetForegroundColor(rgb(payload.palette.0)),
+            SetBackgroundColor(rgb(payload.palette.1))
+        );
+        for x in 0..SCREEN_X {
+            line.push(
+                match (cpu.screen.get(x, row * 2), cpu.screen.get(x, row * 2 + 1)) {
+                    (true, true) => '█',
+                    (true, false) => '▀',
+                    (false, true) => '▄',
+                    (false, false) => ' ',
+                },
+            );
+        }
+        line.push_str(&format!("{}│", ResetColor));
+        queue!(out, cursor::MoveTo(0, row as u16 + 1), Print(line))?;
+    }
+    queue!(
+        out,
+        cursor::MoveTo(0, SCREEN_Y as u16 / 2 + 1),
+        Print(format!("└{}┘", border)),
+        cursor::MoveTo(0, SCREEN_Y as u16 / 2 + 2),
+        Print(format!(
+            "esc: quit   keys: {}   f1-f4: hz  f5: pause  f6: step  f7: slow  f8: turbo  f9: op",
+            KEYPAD
+                .iter()
+                .map(|row| row.iter().map(|key| payload.keymap[*key]).collect())
+                .collect::<Vec<String>>()
+                .join(" ")
+        ))
+    )
+}
+fn draw_clock(out: &mut Stdout, clock: &Clock) -> io::Result<()> {
+    let state = match (clock.paused, clock.speed) {
+        (true, _) => "paused",
+        (false, Speed::Slow) => "slow",
+        (false, Speed::Normal) => "",
+        (false, Speed::Turbo) => "turbo",
+    };
+    queue!(
+        out,
+        cursor::MoveTo(0, SCREEN_Y as u16 / 2 + 3),
+        terminal::Clear(ClearType::CurrentLine),
+        Print(format!("{}hz {}", clock.hz, state))
+    )
+}
+fn draw_registers(out: &mut Stdout, cpu: &Cpu) -> io::Result<()> {
+    for row in 0..8 {
+        queue!(
+            out,
+            cursor::MoveTo(PANE_X, row as u16),
+            Print(format!(
+                "V{:X} {:02X}  V{:X} {:02X}",
+                row,
+                cpu.registers[row] as u8,
+                row + 8,
+                cpu.registers[row + 8] as u8
+            ))
+        )?;
+    }
+    queue!(
+        out,
+        cursor::MoveTo(PANE_X, 9),
+        Print(format!("PC {:04X}  I  {:04X}", cpu.counter, cpu.i)),
+        cursor::MoveTo(PANE_X, 10),
+        Print(format!(
+            "SP {:04X}  DT {:02X}  ST {:02X}",
+            cpu.pointer, cpu.delay, cpu.sound
+        ))
+    )?;
+    for (row, keys) in KEYPAD.iter().enumerate() {
+        let line: String = keys
+            .iter()
+            .map(|&key| {
+                if cpu.keys[key] {
+                    format!("[{:X}]", key)
+                } else {
+                    format!(" {:X} ", key)
+                }
+            })
+            .collect();
+        queue!(out, cursor::MoveTo(PANE_X, 12 + row as u16), Print(line))?;
+    }
+    Ok(())
+}
+/// a few ops either side of the counter, always on even addresses relative to it
+fn draw_disassembly(out: &mut Stdout, cpu: &Cpu) -> io::Result<()> {
+    let x = PANE_X + 24;
+    let start = cpu.counter.saturating_sub(DISASSEMBLY_LINES / 2 * 2);
+    for line in 0..DISASSEMBLY_LINES {
+        let addr = start + line * 2;
+        let marker = if addr == cpu.counter { '>' } else { ' ' };
+        queue!(
+            out,
+            cursor::MoveTo(x, line as u16),
+            terminal::Clear(ClearType::UntilNewLine),
+            Print(format!(
+                "{}{:03X} {}",
+                marker,
+                addr,
+                cpu.instruction_at(addr)
+            ))
+        )?;
+    }
+    Ok(())
+}
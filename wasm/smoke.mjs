@@ -0,0 +1,39 @@
+// node smoke.mjs [path/to/chip_wasm.wasm]
+// loads the release build through the same chip.js the page uses and runs a tiny rom
+import assert from "node:assert/strict";
+import { readFile } from "node:fs/promises";
+import { Chip, SCREEN_X } from "./www/chip.js";
+
+const path = process.argv[2] ?? new URL("target/wasm32-unknown-unknown/release/chip_wasm.wasm", import.meta.url);
+const wasm = await readFile(path);
+
+const rom = new Uint8Array([
+  0x60, 0x10, // 200: LD   V0, 10
+  0xf0, 0x18, // 202: LD   ST, V0
+  0xa2, 0x0a, // 204: LD   I, 20A
+  0xd0, 0x15, // 206: DRW  V0, V1, 5
+  0x12, 0x08, // 208: JP   208
+  0xf0, 0x90, 0x90, 0x90, 0xf0, // 20A: a zero
+]);
+const chip = await Chip.instantiate(wasm);
+chip.load(rom);
+chip.frame(20);
+assert.equal(chip.counter, 0x208);
+assert.ok(chip.beeping, "sound timer should be running");
+// drw reads x and y from V0 and V1, so the zero is at 0x10,0
+const pixel = (x, y) => chip.pixels()[(x + y * SCREEN_X) * 4];
+assert.equal(pixel(0x10, 0), 255);
+assert.equal(pixel(0x13, 4), 255);
+assert.equal(pixel(0x11, 1), 0);
+chip.keyDown(0x5);
+chip.keyUp(0x5);
+for (let i = 0; i < 4; i++) chip.frame(1000);
+assert.ok(!chip.beeping, "sound timer should have run out");
+
+// an undecodable opcode stops the machine on it instead of trapping
+const broken = await Chip.instantiate(wasm);
+broken.load(new Uint8Array([0xff, 0xff]));
+broken.frame(20);
+assert.equal(broken.counter, 0x200);
+
+console.log("ok");
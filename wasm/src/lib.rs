@@ -0,0 +1,90 @@
+// wasm32-unknown-unknown build of the headless core, no bevy and no wasm-bindgen
+// www/chip.js drives it through these plain exports and reads the screen out of linear memory
+// an opcode it can not run stops the machine on it, the counter stays put
+use std::{cell::RefCell, time::Duration};
+
+use chiploxide::chip::{clock::Clock, screen::PIXELS, Cpu};
+
+/// canvas ImageData wants rgba bytes, little endian u32s are abgr
+const ON: u32 = 0xFFFF_FFFF;
+const OFF: u32 = 0xFF00_0000;
+
+struct Machine {
+    cpu: Cpu,
+    clock: Clock,
+    rom: Vec<u8>,
+    bitmap: [u32; PIXELS],
+}
+thread_local! {
+    static MACHINE: RefCell<Machine> = RefCell::new(Machine {
+        cpu: Cpu::new(),
+        clock: Clock::default(),
+        rom: Vec::new(),
+        bitmap: [OFF; PIXELS],
+    });
+}
+fn with<T>(f: impl FnOnce(&mut Machine) -> T) -> T {
+    MACHINE.with(|machine| f(&mut machine.borrow_mut()))
+}
+/// room for a rom of `len` bytes, the shell copies it in and calls `load`
+#[no_mangle]
+pub extern "C" fn rom_buffer(len: usize) -> *mut u8 {
+    with(|machine| {
+        machine.rom = vec![0; len];
+        machine.rom.as_mut_ptr()
+    })
+}
+#[no_mangle]
+pub extern "C" fn load() {
+    with(|machine| {
+        machine.cpu = Cpu::load(&machine.rom);
+        machine.clock = Clock::default();
+    })
+}
+/// runs `ms` worth of instructions and timers, then redraws the bitmap
+#[no_mangle]
+pub extern "C" fn frame(ms: f64) {
+    with(|machine| {
+        let tick = machine
+            .clock
+            .tick(Duration::from_secs_f64(ms.max(0.) / 1000.));
+        machine.cpu.advance(tick);
+        machine.cpu.screen.fill_bitmap(ON, OFF, &mut machine.bitmap);
+    })
+}
+/// SCREEN_X * SCREEN_Y rgba pixels
+#[no_mangle]
+pub extern "C" fn screen() -> *const u32 {
+    with(|machine| machine.bitmap.as_ptr())
+}
+#[no_mangle]
+pub extern "C" fn key_down(key: usize) {
+    with(|machine| set_key(machine, key, true))
+}
+#[no_mangle]
+pub extern "C" fn key_up(key: usize) {
+    with(|machine| set_key(machine, key, false))
+}
+fn set_key(machine: &mut Machine, key: usize, pressed: bool) {
+    if let Some(key) = machine.cpu.keys.get_mut(key) {
+        *key = pressed;
+    }
+}
+/// 1 while the sound timer runs, the shell beeps
+#[no_mangle]
+pub extern "C" fn sound() -> u32 {
+    with(|machine| (machine.cpu.sound > 0) as u32)
+}
+#[no_mangle]
+pub extern "C" fn counter() -> usize {
+    with(|machine| machine.cpu.counter)
+}
+/// std has no entropy on a bare wasm32 target, so the shell seeds CXKK after every load
+#[no_mangle]
+pub extern "C" fn seed(seed: f64) {
+    with(|machine| machine.cpu.seed = seed as u64 | 1)
+}
+#[no_mangle]
+pub extern "C" fn set_hz(hz: u32) {
+    with(|machine| machine.clock.hz = hz.max(1))
+}
@@ -0,0 +1,55 @@
+// thin wrapper over the wasm exports, shared by index.html and smoke.mjs
+export const SCREEN_X = 64;
+export const SCREEN_Y = 32;
+
+// same layout as the terminal and bevy frontends
+//   1 2 3 C     1 2 3 4
+//   4 5 6 D  <- q w e r
+//   7 8 9 E     a s d f
+//   A 0 B F     z x c v
+export const KEYMAP = {
+  1: 0x1, 2: 0x2, 3: 0x3, 4: 0xc,
+  q: 0x4, w: 0x5, e: 0x6, r: 0xd,
+  a: 0x7, s: 0x8, d: 0x9, f: 0xe,
+  z: 0xa, x: 0x0, c: 0xb, v: 0xf,
+};
+
+export class Chip {
+  // `wasm` is the bytes of chip_wasm.wasm, one instance per machine
+  static async instantiate(wasm) {
+    const { instance } = await WebAssembly.instantiate(wasm);
+    return new Chip(instance.exports);
+  }
+  constructor(exports) {
+    this.exports = exports;
+  }
+  load(rom) {
+    const ptr = this.exports.rom_buffer(rom.length);
+    // views go stale whenever memory grows, so always make a fresh one
+    new Uint8Array(this.exports.memory.buffer, ptr, rom.length).set(rom);
+    this.exports.load();
+    this.exports.seed(Math.random() * 2 ** 53);
+  }
+  frame(ms) {
+    this.exports.frame(ms);
+  }
+  keyDown(key) {
+    this.exports.key_down(key);
+  }
+  keyUp(key) {
+    this.exports.key_up(key);
+  }
+  setHz(hz) {
+    this.exports.set_hz(hz);
+  }
+  get beeping() {
+    return this.exports.sound() !== 0;
+  }
+  get counter() {
+    return this.exports.counter();
+  }
+  // rgba, ready for new ImageData(chip.pixels(), SCREEN_X, SCREEN_Y)
+  pixels() {
+    return new Uint8ClampedArray(this.exports.memory.buffer, this.exports.screen(), SCREEN_X * SCREEN_Y * 4);
+  }
+}